@@ -3,18 +3,27 @@ def closest(
     candidates: list[str], 
     algorithm: str = "levenshtein", 
     case_sensitive: bool = False, 
-    remove_whitespace: bool = False) -> str:
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> str:
     """
     Find the closest match to the target string in the list of candidates.
     :param target: The target string to find a match for.
     :param candidates: The list of strings to find a match in.
     :param algorithm: The algorithm to use for finding the closest match. Options are:
         - "levenshtein"
+        - "damerau"
         - "jaro"
         - "jarowinkler"
         - "hamming"
+        - "typo"
     :param case_sensitive: Whether or not to use case sensitivity when finding the closest match.
     :param remove_whitespace: Whether or not to remove whitespace when finding the closest match.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
     """
     ...
 
@@ -24,7 +33,9 @@ def n_closest(
     candidates: list[str], 
     n: int, algorithm: str = "levenshtein", 
     case_senstive: bool = False, 
-    remove_whitespace: bool = False) -> list[str]:
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> list[str]:
     """
     Find the n closest matches to the target string in the list of candidates.
     :param target: The target string to find a match for.
@@ -32,11 +43,18 @@ def n_closest(
     :param n: The number of closest matches to return.
     :param algorithm: The algorithm to use for finding the closest match. Options are:
         - "levenshtein"
+        - "damerau"
         - "jaro"
         - "jarowinkler"
         - "hamming"
+        - "typo"
     :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
     :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
     """
     ...
 
@@ -46,18 +64,22 @@ def closest_index_pair(
     text: str, 
     algorithm: str = "levenshtein", 
     case_sensitive: bool = False, 
-    remove_whitespace: bool = False) -> tuple[int, int]:
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> tuple[int, int]:
     """
     Find the the start and end index of the closest match to the target in the text.
     :param target: The target string to find a match for.
     :param text: The list of strings to find a match in.
     :param algorithm: The algorithm to use for finding the closest match. Options are:
         - "levenshtein"
+        - "damerau"
         - "jaro"
         - "jarowinkler"
         - "hamming"
+        - "typo"
     :param case_sensitive: Whether or not to use case sensitivity when finding the closest match.
     :param remove_whitespace: Whether or not to remove whitespace when finding the closest match.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
     """
     ...
 
@@ -66,19 +88,56 @@ def closest_with_score(
     candidates: list[str], 
     algorithm: str = "levenshtein", 
     case_sensitive: bool = False, 
-    remove_whitespace: bool = False) -> tuple[str, float]:
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> tuple[str, float]:
     """
     Find the closest match to the target string in the list of candidates and the similarity/difference score.
     :param target: The target string to find a match for.
     :param candidates: The list of strings to find a match in.
     :param algorithm: The algorithm to use for finding the closest match. Options are:
         - "levenshtein"
+        - "damerau"
         - "jaro"
         - "jarowinkler"
         - "hamming"
+        - "typo"
     :param case_sensitive: Whether or not to use case sensitivity when finding the closest match.
     :param remove_whitespace: Whether or not to remove whitespace when finding the closest match.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    """
+
+def closest_all(
+    target: str, 
+    candidates: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> list[str]:
+    """
+    Find every candidate that shares the best score for the target string, instead of picking one of them.
+    :param target: The target string to find matches for.
+    :param candidates: The list of strings to find matches in.
+    :param algorithm: The algorithm to use for finding the closest matches. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
+    :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param tie_break: How the tied candidates are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
     """
+    ...
 
 def n_closest_with_score(
     target: str, 
@@ -86,7 +145,9 @@ def n_closest_with_score(
     n: int, 
     algorithm: str = "levenshtein", 
     case_sensitive: bool = False, 
-    remove_whitespace: bool = False) -> list[tuple[str, float]]:
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> list[tuple[str, float]]:
     """
     Find the n closest matches to the target string in the list of candidates and the similarity/difference score.
     :param target: The target string to find a match for.
@@ -94,15 +155,875 @@ def n_closest_with_score(
     :param n: The number of closest matches to return.
     :param algorithm: The algorithm to use for finding the closest match. Options are:
         - "levenshtein"
+        - "damerau"
         - "jaro"
         - "jarowinkler"
         - "hamming"
+        - "typo"
     :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
     :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
     """
     ...
     
     ...
+def closest_many(
+    targets: list[str], 
+    candidates: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> list[str]:
+    """
+    Find the closest match in the list of candidates for each of the targets.
+    :param targets: The target strings to find matches for.
+    :param candidates: The list of strings to find matches in.
+    :param algorithm: The algorithm to use for finding the closest matches. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
+    :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def n_closest_many(
+    targets: list[str], 
+    candidates: list[str], 
+    n: int, 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> list[list[str]]:
+    """
+    Find the n closest matches in the list of candidates for each of the targets.
+    :param targets: The target strings to find matches for.
+    :param candidates: The list of strings to find matches in.
+    :param n: The number of closest matches to return for each target.
+    :param algorithm: The algorithm to use for finding the closest matches. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
+    :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def cdist(
+    queries: list[str], 
+    choices: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> "numpy.ndarray | list[list[float]]":
+    """
+    Score every query against every choice.
+    :param queries: The strings making up the rows of the matrix.
+    :param choices: The strings making up the columns of the matrix.
+    :param algorithm: The algorithm to score with. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when scoring.
+    :param remove_whitespace: Whether or not to remove whitespace when scoring.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    :return: A len(queries) x len(choices) float32 NumPy array if NumPy is installed, otherwise a list of lists.
+    """
+    ...
+
+def closest_async(
+    target: str, 
+    candidates: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> "asyncio.Future[str]":
+    """
+    Awaitable version of closest. Must be called with an event loop running; matching runs on the
+    thread pool and the returned future resolves on that loop without blocking it.
+    :param target: The target string to find a match for.
+    :param candidates: The list of strings to find a match in.
+    :param algorithm: The algorithm to use for finding the closest match. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when finding the closest match.
+    :param remove_whitespace: Whether or not to remove whitespace when finding the closest match.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def n_closest_async(
+    target: str, 
+    candidates: list[str], 
+    n: int, 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    tie_break: str = "original",
+    layout: str | list[str] | None = None) -> "asyncio.Future[list[str]]":
+    """
+    Awaitable version of n_closest. Must be called with an event loop running; matching runs on the
+    thread pool and the returned future resolves on that loop without blocking it.
+    :param target: The target string to find matches for.
+    :param candidates: The list of strings to find matches in.
+    :param n: The number of matches to return.
+    :param algorithm: The algorithm to use for finding the closest matches. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when finding the closest matches.
+    :param remove_whitespace: Whether or not to remove whitespace when finding the closest matches.
+    :param tie_break: How candidates with equal scores are ordered. Options are:
+        - "original": the order they were given in
+        - "shortest": shorter candidates first
+        - "lexicographic": alphabetical order
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def cdist_async(
+    queries: list[str], 
+    choices: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> "asyncio.Future[numpy.ndarray | list[list[float]]]":
+    """
+    Awaitable version of cdist. Must be called with an event loop running; scoring runs on the
+    thread pool and the returned future resolves on that loop without blocking it.
+    :param queries: The strings making up the rows of the matrix.
+    :param choices: The strings making up the columns of the matrix.
+    :param algorithm: The algorithm to score with. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when scoring.
+    :param remove_whitespace: Whether or not to remove whitespace when scoring.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def set_num_threads(num_threads: int) -> None:
+    """
+    Set the number of worker threads used by the finders, cdist, dedupe, cluster, fuzzy_join, assignment and the
+    Index, BKTree, SymSpell, Trie and QGramIndex classes.
+    :param num_threads: The number of threads. 1 runs everything on a single worker thread, for use inside
+        processes that are already parallel, and 0 goes back to the default pool sized by RAYON_NUM_THREADS
+        or the number of CPUs.
+    """
+    ...
+
+def get_num_threads() -> int:
+    """
+    The number of worker threads used by the finders, cdist, dedupe, cluster, fuzzy_join, assignment and the indexes.
+    """
+    ...
+
+def set_parallel_threshold(threshold: int) -> None:
+    """
+    Set the number of candidates below which matching runs sequentially instead of being split between threads.
+    :param threshold: The number of candidates. Defaults to 1000.
+    """
+    ...
+
+def get_parallel_threshold() -> int:
+    """
+    The number of candidates below which matching runs sequentially.
+    """
+    ...
+
+def dedupe(
+    strings: list[str], 
+    algorithm: str = "levenshtein", 
+    cutoff: float = 1.0, 
+    canonical: str = "first", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    threshold: float = 0.0, 
+    layout: str | list[str] | None = None) -> list[tuple[str, list[str]]]:
+    """
+    Group near-duplicate strings.
+    :param strings: The strings to deduplicate.
+    :param algorithm: The algorithm to compare the strings with. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming" (strings of different lengths are never duplicates)
+        - "typo"
+    :param cutoff: Two strings are duplicates when their distance is at most the cutoff,
+        or their similarity is at least the cutoff. Chains of duplicates form one group.
+    :param canonical: How to pick the representative of each group. Options are:
+        - "first": the first member in input order
+        - "longest": the longest member
+        - "shortest": the shortest member
+        - "central": the member with the best total score against the rest of the group
+    :param case_sensitive: Whether or not to use case sensitivity when comparing the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when comparing the strings.
+    :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    :return: A (canonical, members) tuple for every group of two or more strings, members in input order.
+    """
+    ...
+
+def cluster(
+    strings: list[str], 
+    algorithm: str = "levenshtein", 
+    cutoff: float = 1.0, 
+    method: str = "connected", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    threshold: float = 0.0, 
+    layout: str | list[str] | None = None) -> list[int]:
+    """
+    Cluster strings by similarity.
+    :param strings: The strings to cluster.
+    :param algorithm: The algorithm to compare the strings with. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming" (strings of different lengths are never linked)
+        - "typo"
+    :param cutoff: Two strings are linked when their distance is at most the cutoff,
+        or their similarity is at least the cutoff.
+    :param method: The clustering method. Options are:
+        - "connected" or "single": connected components of linked strings (single-linkage clustering cut at the cutoff)
+        - "leader": each string joins the closest linked cluster leader, or leads a new cluster
+    :param case_sensitive: Whether or not to use case sensitivity when comparing the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when comparing the strings.
+    :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    :return: A cluster id for every string, numbered in order of first appearance.
+    """
+    ...
+
+def fuzzy_join(
+    left: list[str], 
+    right: list[str], 
+    algorithm: str = "levenshtein", 
+    cutoff: float | None = None, 
+    how: str = "inner", 
+    one_to_one: bool = False, 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    threshold: float = 0.0, 
+    layout: str | list[str] | None = None) -> list[tuple[int, int | None, float | None]]:
+    """
+    Join each string in left to its best match in right.
+    :param left: The strings to find matches for.
+    :param right: The strings to find matches in.
+    :param algorithm: The algorithm to compare the strings with. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming" (strings of different lengths never match)
+        - "typo"
+    :param cutoff: If given, matches must have a distance of at most the cutoff, or a similarity of at least the cutoff.
+    :param how: "inner" to drop unmatched left rows, "left" to keep them as (left_index, None, None).
+    :param one_to_one: Whether each right row may be used only once. Conflicts go to the pair with the best score.
+    :param case_sensitive: Whether or not to use case sensitivity when comparing the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when comparing the strings.
+    :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    :return: (left_index, right_index, score) rows ordered by left index.
+    """
+    ...
+
+def assignment(
+    left: list[str], 
+    right: list[str], 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False, 
+    threshold: float = 0.0, 
+    layout: str | list[str] | None = None) -> list[tuple[int, int, float]]:
+    """
+    Find the globally optimal one-to-one pairing between two lists (Hungarian algorithm).
+    :param left: The first list of strings.
+    :param right: The second list of strings.
+    :param algorithm: The algorithm to compare the strings with. Distances are minimized and similarities maximized. Options are:
+        - "levenshtein"
+        - "damerau"
+        - "jaro"
+        - "jarowinkler"
+        - "hamming"
+        - "typo"
+    :param case_sensitive: Whether or not to use case sensitivity when comparing the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when comparing the strings.
+    :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+    :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    :return: min(len(left), len(right)) (left_index, right_index, score) rows ordered by left index.
+    """
+    ...
+
+class Index:
+    """
+    A list of candidates preprocessed once and kept in Rust, to be searched repeatedly
+    without converting and preprocessing the candidates on every call.
+    """
+
+    candidates: list[str]
+    case_sensitive: bool
+    remove_whitespace: bool
+
+    def __init__(
+        self, 
+        candidates: list[str], 
+        case_sensitive: bool = False, 
+        remove_whitespace: bool = False) -> None:
+        """
+        :param candidates: The list of strings to find matches in.
+        :param case_sensitive: Whether or not to use case sensitivity when finding matches.
+        :param remove_whitespace: Whether or not to remove whitespace when finding matches.
+        """
+        ...
+
+    def add(self, candidate: str) -> None:
+        """
+        Add a candidate after the existing ones.
+        :param candidate: The string to add.
+        """
+        ...
+
+    def remove(self, candidate: str) -> None:
+        """
+        Remove every copy of a candidate.
+        :param candidate: The string to remove.
+        :raises ValueError: If the candidate is not in the index.
+        """
+        ...
+
+    def update(self, old: str, new: str) -> None:
+        """
+        Replace every copy of a candidate with another, keeping their positions.
+        :param old: The string to replace.
+        :param new: The string to replace it with.
+        :raises ValueError: If the old candidate is not in the index.
+        """
+        ...
+
+    def save(self, path: str) -> None:
+        """
+        Write the Index to a versioned, checksummed binary file.
+        :param path: The file to write.
+        """
+        ...
+
+    @staticmethod
+    def load(path: str) -> "Index":
+        """
+        Load a Index written by save, without rebuilding it.
+        The whole file is read and decoded into memory. Memory-mapping index files is not supported.
+        :param path: The file to read.
+        :raises ValueError: If the file is not a Index index, was written by an unsupported format version, fails its checksum or is corrupt.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+    def closest(
+        self, 
+        target: str, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        tie_break: str = "original", 
+        layout: str | list[str] | None = None) -> str:
+        """
+        Find the closest match to the target string in the indexed candidates.
+        :param target: The target string to find a match for.
+        :param algorithm: The algorithm to use for finding the closest match. Options are:
+            - "levenshtein"
+            - "damerau"
+            - "jaro"
+            - "jarowinkler"
+            - "hamming"
+            - "typo"
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param tie_break: How candidates with equal scores are ordered. Options are:
+            - "original": the order they were added in
+            - "shortest": shorter candidates first
+            - "lexicographic": alphabetical order
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
+    def closest_with_score(
+        self, 
+        target: str, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        tie_break: str = "original", 
+        layout: str | list[str] | None = None) -> tuple[str, float]:
+        """
+        Find the closest match to the target string in the indexed candidates and the similarity/difference score.
+        :param target: The target string to find a match for.
+        :param algorithm: The algorithm to use for finding the closest match.
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param tie_break: How candidates with equal scores are ordered. Options are:
+            - "original": the order they were added in
+            - "shortest": shorter candidates first
+            - "lexicographic": alphabetical order
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
+    def n_closest(
+        self, 
+        target: str, 
+        n: int, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        tie_break: str = "original", 
+        layout: str | list[str] | None = None) -> list[str]:
+        """
+        Find the n closest matches to the target string in the indexed candidates.
+        :param target: The target string to find matches for.
+        :param n: The number of closest matches to return.
+        :param algorithm: The algorithm to use for finding the closest matches.
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param tie_break: How candidates with equal scores are ordered. Options are:
+            - "original": the order they were added in
+            - "shortest": shorter candidates first
+            - "lexicographic": alphabetical order
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
+    def n_closest_with_score(
+        self, 
+        target: str, 
+        n: int, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        tie_break: str = "original", 
+        layout: str | list[str] | None = None) -> list[tuple[str, float]]:
+        """
+        Find the n closest matches to the target string in the indexed candidates and the similarity/difference scores.
+        :param target: The target string to find matches for.
+        :param n: The number of closest matches to return.
+        :param algorithm: The algorithm to use for finding the closest matches.
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param tie_break: How candidates with equal scores are ordered. Options are:
+            - "original": the order they were added in
+            - "shortest": shorter candidates first
+            - "lexicographic": alphabetical order
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
+class BKTree:
+    """
+    A BK-tree over a list of candidates for fast lookups under a metric distance.
+    """
+
+    algorithm: str
+
+    def __init__(
+        self, 
+        candidates: list[str], 
+        algorithm: str = "levenshtein", 
+        case_sensitive: bool = False, 
+        remove_whitespace: bool = False) -> None:
+        """
+        :param candidates: The list of strings to search.
+        :param algorithm: The metric to index the candidates with. Options are:
+            - "levenshtein"
+            - "damerau"
+            - "hamming" (only candidates of the same length as the query are searched)
+        :param case_sensitive: Whether or not to use case sensitivity when comparing strings.
+        :param remove_whitespace: Whether or not to remove whitespace when comparing strings.
+        """
+        ...
+
+    def add(self, candidate: str) -> None:
+        """
+        Add a candidate after the existing ones.
+        :param candidate: The string to add.
+        """
+        ...
+
+    def remove(self, candidate: str) -> None:
+        """
+        Remove every copy of a candidate.
+        :param candidate: The string to remove.
+        :raises ValueError: If the candidate is not in the tree.
+        """
+        ...
+
+    def update(self, old: str, new: str) -> None:
+        """
+        Replace every copy of a candidate with another, keeping their positions.
+        :param old: The string to replace.
+        :param new: The string to replace it with.
+        :raises ValueError: If the old candidate is not in the tree.
+        """
+        ...
+
+    def save(self, path: str) -> None:
+        """
+        Write the BKTree to a versioned, checksummed binary file.
+        :param path: The file to write.
+        """
+        ...
+
+    @staticmethod
+    def load(path: str) -> "BKTree":
+        """
+        Load a BKTree written by save, without rebuilding it.
+        The whole file is read and decoded into memory. Memory-mapping index files is not supported.
+        :param path: The file to read.
+        :raises ValueError: If the file is not a BKTree index, was written by an unsupported format version, fails its checksum or is corrupt.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+    def search(self, query: str, max_distance: int) -> list[tuple[str, float]]:
+        """
+        Find every candidate within max_distance of the query.
+        :param query: The string to search for.
+        :param max_distance: The largest distance to include.
+        :return: (candidate, distance) pairs ordered by distance, then by position in the candidates.
+        """
+        ...
+
+    def nearest(self, query: str, k: int) -> list[tuple[str, float]]:
+        """
+        Find the k candidates closest to the query.
+        :param query: The string to search for.
+        :param k: The number of candidates to return.
+        :return: (candidate, distance) pairs ordered by distance, then by position in the candidates.
+        """
+        ...
+
+class SymSpell:
+    """
+    A symmetric-delete spelling correction index returning every dictionary word within a few edits of a query.
+    """
+
+    max_distance: int
+
+    def __init__(
+        self, 
+        words: list[str], 
+        max_distance: int = 2, 
+        frequencies: list[int] | None = None, 
+        case_sensitive: bool = False, 
+        remove_whitespace: bool = False) -> None:
+        """
+        :param words: The dictionary words. Words that are equal after preprocessing are merged, adding up their frequencies.
+        :param max_distance: The largest Levenshtein distance that can be looked up.
+        :param frequencies: How common each word is, used to order words at the same distance. Defaults to 1 per word.
+        :param case_sensitive: Whether or not to use case sensitivity when comparing strings.
+        :param remove_whitespace: Whether or not to remove whitespace when comparing strings.
+        """
+        ...
+
+    def add(self, word: str, frequency: int = 1) -> None:
+        """
+        Add a word. A word equal to one in the index after preprocessing adds its frequency to that word instead.
+        :param word: The string to add.
+        :param frequency: How common the word is.
+        """
+        ...
+
+    def remove(self, word: str) -> None:
+        """
+        Remove a word, compared after preprocessing.
+        :param word: The string to remove.
+        :raises ValueError: If the word is not in the index.
+        """
+        ...
+
+    def update(self, old: str, new: str) -> None:
+        """
+        Replace a word with another, keeping its frequency. If the new word is already in the index,
+        the old word's frequency is added to it and the old word is removed.
+        :param old: The string to replace.
+        :param new: The string to replace it with.
+        :raises ValueError: If the old word is not in the index.
+        """
+        ...
+
+    def save(self, path: str) -> None:
+        """
+        Write the SymSpell to a versioned, checksummed binary file.
+        :param path: The file to write.
+        """
+        ...
+
+    @staticmethod
+    def load(path: str) -> "SymSpell":
+        """
+        Load a SymSpell written by save, without rebuilding it.
+        The whole file is read and decoded into memory. Memory-mapping index files is not supported.
+        :param path: The file to read.
+        :raises ValueError: If the file is not a SymSpell index, was written by an unsupported format version, fails its checksum or is corrupt.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+    def lookup(self, query: str, max_distance: int | None = None) -> list[tuple[str, float]]:
+        """
+        Find every dictionary word within max_distance edits of the query.
+        :param query: The string to correct.
+        :param max_distance: The largest distance to include, at most (and by default) the index max_distance.
+        :return: (word, distance) pairs ordered by distance, then by descending frequency.
+        """
+        ...
+
+    def correct(self, query: str) -> str | None:
+        """
+        Return the closest, most frequent dictionary word within the index max_distance of the query, or None.
+        :param query: The string to correct.
+        """
+        ...
+
+class Trie:
+    """
+    A trie over a list of candidates, searched with the Levenshtein automaton of the query
+    so that only candidates sharing a close enough prefix are visited.
+    """
+
+    def __init__(
+        self, 
+        candidates: list[str], 
+        case_sensitive: bool = False, 
+        remove_whitespace: bool = False) -> None:
+        """
+        :param candidates: The list of strings to search.
+        :param case_sensitive: Whether or not to use case sensitivity when comparing strings.
+        :param remove_whitespace: Whether or not to remove whitespace when comparing strings.
+        """
+        ...
+
+    def add(self, candidate: str) -> None:
+        """
+        Add a candidate after the existing ones.
+        :param candidate: The string to add.
+        """
+        ...
+
+    def remove(self, candidate: str) -> None:
+        """
+        Remove every copy of a candidate.
+        :param candidate: The string to remove.
+        :raises ValueError: If the candidate is not in the trie.
+        """
+        ...
+
+    def update(self, old: str, new: str) -> None:
+        """
+        Replace every copy of a candidate with another, keeping their positions.
+        :param old: The string to replace.
+        :param new: The string to replace it with.
+        :raises ValueError: If the old candidate is not in the trie.
+        """
+        ...
+
+    def save(self, path: str) -> None:
+        """
+        Write the Trie to a versioned, checksummed binary file.
+        :param path: The file to write.
+        """
+        ...
+
+    @staticmethod
+    def load(path: str) -> "Trie":
+        """
+        Load a Trie written by save, without rebuilding it.
+        The whole file is read and decoded into memory. Memory-mapping index files is not supported.
+        :param path: The file to read.
+        :raises ValueError: If the file is not a Trie index, was written by an unsupported format version, fails its checksum or is corrupt.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+    def search(self, query: str, max_distance: int) -> list[tuple[str, float]]:
+        """
+        Find every candidate within max_distance Levenshtein edits of the query.
+        :param query: The string to search for.
+        :param max_distance: The largest distance to include.
+        :return: (candidate, distance) pairs ordered by distance, then by position in the candidates.
+        """
+        ...
+
+    def closest(self, target: str) -> str:
+        """
+        Find the candidate with the smallest Levenshtein distance to the target.
+        :param target: The string to compare the candidates to.
+        """
+        ...
+
+    def closest_with_score(self, target: str) -> tuple[str, float]:
+        """
+        Find the candidate with the smallest Levenshtein distance to the target and the distance.
+        :param target: The string to compare the candidates to.
+        """
+        ...
+
+    def n_closest(self, target: str, n: int) -> list[str]:
+        """
+        Find the n candidates with the smallest Levenshtein distances to the target.
+        :param target: The string to compare the candidates to.
+        :param n: The number of candidates to return.
+        """
+        ...
+
+    def n_closest_with_score(self, target: str, n: int) -> list[tuple[str, float]]:
+        """
+        Find the n candidates with the smallest Levenshtein distances to the target and their distances.
+        :param target: The string to compare the candidates to.
+        :param n: The number of candidates to return.
+        """
+        ...
+
+class QGramIndex:
+    """
+    An inverted index from q-grams to candidates, for fast lookups in large corpora of long strings.
+    """
+
+    q: int
+
+    def __init__(
+        self, 
+        candidates: list[str], 
+        q: int = 3, 
+        case_sensitive: bool = False, 
+        remove_whitespace: bool = False) -> None:
+        """
+        :param candidates: The list of strings to search.
+        :param q: The length of the q-grams to index.
+        :param case_sensitive: Whether or not to use case sensitivity when comparing strings.
+        :param remove_whitespace: Whether or not to remove whitespace when comparing strings.
+        """
+        ...
+
+    def add(self, candidate: str) -> None:
+        """
+        Add a candidate after the existing ones.
+        :param candidate: The string to add.
+        """
+        ...
+
+    def remove(self, candidate: str) -> None:
+        """
+        Remove every copy of a candidate.
+        :param candidate: The string to remove.
+        :raises ValueError: If the candidate is not in the index.
+        """
+        ...
+
+    def update(self, old: str, new: str) -> None:
+        """
+        Replace every copy of a candidate with another, keeping their positions.
+        :param old: The string to replace.
+        :param new: The string to replace it with.
+        :raises ValueError: If the old candidate is not in the index.
+        """
+        ...
+
+    def save(self, path: str) -> None:
+        """
+        Write the QGramIndex to a versioned, checksummed binary file.
+        :param path: The file to write.
+        """
+        ...
+
+    @staticmethod
+    def load(path: str) -> "QGramIndex":
+        """
+        Load a QGramIndex written by save, without rebuilding it.
+        The whole file is read and decoded into memory. Memory-mapping index files is not supported.
+        :param path: The file to read.
+        :raises ValueError: If the file is not a QGramIndex index, was written by an unsupported format version, fails its checksum or is corrupt.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+    def search(self, query: str, max_distance: int) -> list[tuple[str, float]]:
+        """
+        Find every candidate within max_distance Levenshtein edits of the query.
+        :param query: The string to search for.
+        :param max_distance: The largest distance to include.
+        :return: (candidate, distance) pairs ordered by distance, then by position in the candidates.
+        """
+        ...
+
+    def n_closest(
+        self, 
+        target: str, 
+        n: int, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        min_shared: int = 1, 
+        layout: str | list[str] | None = None) -> list[str]:
+        """
+        Find up to n closest matches to the target among the candidates sharing at least min_shared q-grams with it.
+        :param target: The string to compare the candidates to.
+        :param n: The largest number of candidates to return.
+        :param algorithm: The algorithm to re-rank the shortlisted candidates with.
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param min_shared: The number of q-grams a candidate must share with the target to be scored.
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
+    def n_closest_with_score(
+        self, 
+        target: str, 
+        n: int, 
+        algorithm: str = "levenshtein", 
+        threshold: float = 0.0, 
+        min_shared: int = 1, 
+        layout: str | list[str] | None = None) -> list[tuple[str, float]]:
+        """
+        Find up to n closest matches to the target among the candidates sharing at least min_shared q-grams with it,
+        and their scores.
+        :param target: The string to compare the candidates to.
+        :param n: The largest number of candidates to return.
+        :param algorithm: The algorithm to re-rank the shortlisted candidates with.
+        :param threshold: The threshold to use for the Jaro-Winkler similarity algorithm.
+        :param min_shared: The number of q-grams a candidate must share with the target to be scored.
+        :param layout: The keyboard layout for the "typo" algorithm, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+        """
+        ...
+
 def levenshtein_distance(
     a: str, 
     b: str, 
@@ -117,6 +1038,20 @@ def levenshtein_distance(
     """
     ...
 
+def damerau_levenshtein_distance(
+    a: str, 
+    b: str, 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False) -> int:
+    """
+    Calculate the Damerau-Levenshtein distance between two strings, where swapping two adjacent characters is a single edit.
+    :param a: The first string to compare.
+    :param b: The second string to compare.
+    :param case_sensitive: Whether or not to use case sensitivity when calculating the Damerau-Levenshtein distance.
+    :param remove_whitespace: Whether or not to remove whitespace when calculating the Damerau-Levenshtein distance.
+    """
+    ...
+
 def jaro_similarity(
     a: str, 
     b: str, 
@@ -158,3 +1093,136 @@ def hamming_distance(
     :param remove_whitespace: Whether or not to remove whitespace when calculating the Hamming distance.
     """
     ...
+
+def typo_distance(
+    a: str, 
+    b: str, 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    layout: str | list[str] | None = None) -> float:
+    """
+    Calculate a Levenshtein distance where substituting a key with a neighboring key costs less than a full edit.
+    :param a: The first string to compare.
+    :param b: The second string to compare.
+    :param case_sensitive: Whether or not to use case sensitivity when calculating the typo distance.
+    :param remove_whitespace: Whether or not to remove whitespace when calculating the typo distance.
+    :param layout: The keyboard layout, either "qwerty" (default), "azerty", "dvorak" or a list of keyboard rows from top to bottom.
+    """
+    ...
+
+def editops(
+    a: str, 
+    b: str, 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False) -> list[tuple[str, int, int]]:
+    """
+    List the edit operations that turn one string into another.
+    :param a: The source string.
+    :param b: The destination string.
+    :param case_sensitive: Whether or not to use case sensitivity when computing the edit operations.
+    :param remove_whitespace: Whether or not to remove whitespace when computing the edit operations.
+    :return: A list of (tag, src_pos, dest_pos) tuples where tag is "replace", "insert" or "delete".
+    """
+    ...
+
+def opcodes(
+    a: str, 
+    b: str, 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False) -> list[tuple[str, int, int, int, int]]:
+    """
+    List the difflib-compatible opcodes that turn one string into another.
+    :param a: The source string.
+    :param b: The destination string.
+    :param case_sensitive: Whether or not to use case sensitivity when computing the opcodes.
+    :param remove_whitespace: Whether or not to remove whitespace when computing the opcodes.
+    :return: A list of (tag, i1, i2, j1, j2) tuples where tag is "equal", "replace", "insert" or "delete".
+    """
+    ...
+
+def matching_blocks(
+    a: str, 
+    b: str, 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False) -> list[tuple[int, int, int]]:
+    """
+    List the matching blocks in the alignment of two strings, in the same format as difflib.
+    :param a: The source string.
+    :param b: The destination string.
+    :param algorithm: The algorithm to align the strings with. Options are:
+        - "levenshtein"
+        - "lcs" (longest common subsequence, aligning without substitutions. Only the alignment
+          functions accept it; it is not a scoring algorithm for the finders.)
+    :param case_sensitive: Whether or not to use case sensitivity when aligning the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when aligning the strings.
+    :return: A list of (i, j, size) tuples ending with (len(a), len(b), 0).
+    """
+    ...
+
+def alignment(
+    a: str, 
+    b: str, 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    gap: str = "-") -> tuple[str, str, str]:
+    """
+    Align two strings.
+    :param a: The source string.
+    :param b: The destination string.
+    :param algorithm: The algorithm to align the strings with. Options are:
+        - "levenshtein"
+        - "lcs" (longest common subsequence, aligning without substitutions. Only the alignment
+          functions accept it; it is not a scoring algorithm for the finders.)
+    :param case_sensitive: Whether or not to use case sensitivity when aligning the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when aligning the strings.
+    :param gap: The character used to mark gaps in the aligned strings.
+    :return: The aligned a, a match line ("|" for equal, "." for substituted, " " for gaps) and the aligned b.
+    """
+    ...
+
+def format_alignment(
+    a: str, 
+    b: str, 
+    algorithm: str = "levenshtein", 
+    case_sensitive: bool = False, 
+    remove_whitespace: bool = False,
+    gap: str = "-") -> str:
+    """
+    Render the alignment of two strings as three lines of text.
+    :param a: The source string.
+    :param b: The destination string.
+    :param algorithm: The algorithm to align the strings with. Options are:
+        - "levenshtein"
+        - "lcs" (longest common subsequence, aligning without substitutions. Only the alignment
+          functions accept it; it is not a scoring algorithm for the finders.)
+    :param case_sensitive: Whether or not to use case sensitivity when aligning the strings.
+    :param remove_whitespace: Whether or not to remove whitespace when aligning the strings.
+    :param gap: The character used to mark gaps in the aligned strings.
+    """
+    ...
+
+def apply_editops(
+    source: str, 
+    ops: list[tuple[str, int, int]], 
+    dest: str,
+    case_sensitive: bool = False,
+    remove_whitespace: bool = False) -> str:
+    """
+    Apply edit operations to a string.
+    :param source: The string to edit.
+    :param ops: Edit operations from editops(source, dest), or any ordered subset of them.
+    :param dest: The string the edit operations lead to, used for inserted and replaced characters.
+    :param case_sensitive: The case_sensitive setting the edit operations were made with.
+    :param remove_whitespace: The remove_whitespace setting the edit operations were made with. Removed whitespace is kept from source.
+    :return: The edited string. Characters outside the edited positions are kept from source unchanged.
+    """
+    ...
+
+def inverse_editops(ops: list[tuple[str, int, int]]) -> list[tuple[str, int, int]]:
+    """
+    Invert edit operations so that they turn the destination string back into the source string.
+    :param ops: Edit operations from editops(source, dest).
+    """
+    ...
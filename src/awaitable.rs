@@ -0,0 +1,185 @@
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use crate::finder::{cdist_scores, matrix_to_py, n_best_options};
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py};
+use crate::parallel::spawn;
+use pyo3::{
+    prelude::*,
+    types::{PyCFunction, PyDict, PyTuple},
+};
+
+type Resolution = Box<dyn FnOnce(Python) + Send>;
+
+/// The sender of a thread that takes the GIL to resolve finished jobs. A pool thread must never wait
+/// for the GIL itself: a caller holding the GIL while it waits for the pool would then deadlock with it.
+fn resolver() -> &'static Sender<Resolution> {
+    static RESOLVER: OnceLock<Sender<Resolution>> = OnceLock::new();
+    RESOLVER.get_or_init(|| {
+        let (sender, receiver) = channel::<Resolution>();
+        thread::spawn(move || {
+            for resolution in receiver {
+                Python::with_gil(resolution);
+            }
+        });
+        sender
+    })
+}
+
+/// Run a job on the thread pool and return an asyncio future, created on the running event loop,
+/// that the job's result resolves. Once the job is done, the resolver thread converts the result to
+/// a Python object and hands it to the loop thread, which sets it unless the future was cancelled.
+fn spawn_future<T, F, C>(py: Python, job: F, convert: C) -> PyResult<PyObject>
+where
+    T: Send + 'static,
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+    C: FnOnce(Python, T) -> PyResult<PyObject> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future: PyObject = event_loop.call_method0("create_future")?.into();
+    let event_loop: PyObject = event_loop.into();
+    let pending = future.clone_ref(py);
+    spawn(move || {
+        let result = job();
+        let resolution: Resolution = Box::new(move |py| {
+            let outcome = Mutex::new(Some(result.and_then(|value| convert(py, value))));
+            let resolve = move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                let py = args.py();
+                let future = pending.as_ref(py);
+                // A future cancelled while the job ran must be left alone.
+                if future.call_method0("done")?.is_true()? {
+                    return Ok(());
+                }
+                match outcome.lock().unwrap_or_else(|error| error.into_inner()).take() {
+                    Some(Ok(value)) => {
+                        future.call_method1("set_result", (value,))?;
+                    }
+                    Some(Err(error)) => {
+                        future.call_method1("set_exception", (error.instance(py),))?;
+                    }
+                    None => {}
+                }
+                Ok(())
+            };
+            let scheduled = PyCFunction::new_closure(resolve, py)
+                .and_then(|resolve| event_loop.call_method1(py, "call_soon_threadsafe", (resolve,)));
+            // Scheduling only fails once the loop has been closed, and then nothing is left waiting on the future.
+            drop(scheduled);
+        });
+        // The resolver thread never stops, so its receiver is never dropped.
+        drop(resolver().send(resolution));
+    });
+    Ok(future)
+}
+
+/// closest_async(target, candidates, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, tie_break='original', layout=None)
+/// --
+///
+/// Awaitable version of closest. Matching runs on the thread pool and the returned future
+/// resolves on the running event loop, which is not blocked in the meantime.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn closest_async(
+    py: Python,
+    target: String,
+    options: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let (algorithm, tie_break) = (algorithm.to_string(), tie_break.to_string());
+    let layout = optional_layout_from_py(py, layout)?;
+    spawn_future(
+        py,
+        move || {
+            let layout = layout_or_qwerty(&layout);
+            n_best_options(&target, &options, 1, &algorithm, case_sensitive, remove_whitespace, threshold, &tie_break, layout)
+        },
+        |py, mut closest| Ok(closest.remove(0).0.into_py(py)),
+    )
+}
+
+/// n_closest_async(target, candidates, n, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, tie_break='original', layout=None)
+/// --
+///
+/// Awaitable version of n_closest. Matching runs on the thread pool and the returned future
+/// resolves on the running event loop, which is not blocked in the meantime.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn n_closest_async(
+    py: Python,
+    target: String,
+    options: Vec<String>,
+    n: usize,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let (algorithm, tie_break) = (algorithm.to_string(), tie_break.to_string());
+    let layout = optional_layout_from_py(py, layout)?;
+    spawn_future(
+        py,
+        move || {
+            let layout = layout_or_qwerty(&layout);
+            n_best_options(&target, &options, n, &algorithm, case_sensitive, remove_whitespace, threshold, &tie_break, layout)
+        },
+        |py, closest| Ok(closest.into_iter().map(|(option, _)| option).collect::<Vec<_>>().into_py(py)),
+    )
+}
+
+/// cdist_async(queries, choices, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Awaitable version of cdist. Scoring runs on the thread pool and the returned future
+/// resolves on the running event loop, which is not blocked in the meantime.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn cdist_async(
+    py: Python,
+    queries: Vec<String>,
+    choices: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let algorithm = algorithm.to_string();
+    let layout = optional_layout_from_py(py, layout)?;
+    let (rows, cols) = (queries.len(), choices.len());
+    spawn_future(
+        py,
+        move || {
+            let layout = layout_or_qwerty(&layout);
+            cdist_scores(&queries, &choices, &algorithm, case_sensitive, remove_whitespace, threshold, layout)
+        },
+        move |py, scores| matrix_to_py(py, scores, rows, cols),
+    )
+}
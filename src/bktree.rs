@@ -0,0 +1,396 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::RwLock;
+
+use crate::finder::{get_matched_scorer, MatchedScorer};
+use crate::parallel::install;
+use crate::persist::{
+    check_candidate, check_count, check_position, corrupt, load_index, save_index, Persist, Reader, StoredIndex,
+};
+use crate::utils::{candidate_positions, char_vec, read_lock, write_lock};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+struct Node {
+    // The candidate the node was created for. Searches are routed by the distance to it,
+    // so it is kept even after that candidate is removed or updated.
+    pivot: Vec<char>,
+    entries: Vec<usize>,
+    children: Vec<(u32, usize)>,
+}
+
+impl Persist for Node {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.pivot.write(out);
+        self.entries.write(out);
+        self.children.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(Node { pivot: Persist::read(input)?, entries: Persist::read(input)?, children: Persist::read(input)? })
+    }
+}
+
+struct TreeStore {
+    // Removed candidates leave a None behind so that the positions of the others do not change.
+    candidates: Vec<Option<String>>,
+    nodes: Vec<Node>,
+    roots: HashMap<usize, usize>,
+    count: usize,
+}
+
+impl TreeStore {
+    fn candidate(&self, index: usize) -> String {
+        self.candidates[index]
+            .clone()
+            .expect("Tree nodes only hold candidates that have not been removed.")
+    }
+
+    // Children are always created after their parent, so a child before its parent would mean a cycle.
+    fn validate(&self) -> PyResult<()> {
+        for (position, node) in self.nodes.iter().enumerate() {
+            for &i in &node.entries {
+                check_candidate(i, &self.candidates)?;
+            }
+            for &(_, child) in &node.children {
+                check_position(child, self.nodes.len(), "node")?;
+                if child <= position {
+                    return Err(corrupt(&format!("node {} is a child of node {}.", child, position)));
+                }
+            }
+        }
+        for &root in self.roots.values() {
+            check_position(root, self.nodes.len(), "node")?;
+        }
+        check_count(self.count, &self.candidates)
+    }
+}
+
+impl Persist for TreeStore {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.candidates.write(out);
+        self.nodes.write(out);
+        self.roots.write(out);
+        self.count.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = TreeStore {
+            candidates: Persist::read(input)?,
+            nodes: Persist::read(input)?,
+            roots: Persist::read(input)?,
+            count: Persist::read(input)?,
+        };
+        store.validate()?;
+        Ok(store)
+    }
+}
+
+/// BKTree(candidates, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// A BK-tree over the candidates for fast lookups under a metric distance
+/// ("levenshtein", "damerau" or "hamming"). Candidates can be added, removed and updated
+/// without rebuilding the tree.
+#[pyclass]
+pub struct BKTree {
+    store: RwLock<TreeStore>,
+    algorithm: String,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+}
+
+impl BKTree {
+    fn scorer(&self) -> MatchedScorer {
+        get_matched_scorer(&self.algorithm)
+    }
+
+    // Hamming distance is only defined between strings of the same length,
+    // so each length gets a tree of its own.
+    fn root_key(&self, chars: &[char]) -> usize {
+        if self.algorithm == "HAMMING" {
+            chars.len()
+        } else {
+            0
+        }
+    }
+
+    fn distance(&self, a: &[char], b: &[char]) -> PyResult<u32> {
+        Ok((self.scorer())(a, b, 0.0)? as u32)
+    }
+
+    fn insert(&self, store: &mut TreeStore, index: usize, chars: Vec<char>) -> PyResult<()> {
+        let key = self.root_key(&chars);
+        let mut node = match store.roots.get(&key) {
+            Some(&root) => root,
+            None => {
+                store.roots.insert(key, store.nodes.len());
+                store.nodes.push(Node { pivot: chars, entries: vec![index], children: Vec::new() });
+                return Ok(());
+            }
+        };
+        loop {
+            let distance = self.distance(&store.nodes[node].pivot, &chars)?;
+            if distance == 0 {
+                store.nodes[node].entries.push(index);
+                return Ok(());
+            }
+            match store.nodes[node].children.iter().find(|(d, _)| *d == distance) {
+                Some(&(_, child)) => node = child,
+                None => {
+                    let child = store.nodes.len();
+                    store.nodes.push(Node { pivot: chars, entries: vec![index], children: Vec::new() });
+                    store.nodes[node].children.push((distance, child));
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // A candidate is an entry of the node whose pivot is at distance 0 from it, found along the same path
+    // it was inserted by. The node stays in the tree to route searches to its children.
+    fn detach(&self, store: &mut TreeStore, index: usize, chars: &[char]) -> PyResult<()> {
+        let mut node = match store.roots.get(&self.root_key(chars)) {
+            Some(&root) => root,
+            None => return Ok(()),
+        };
+        loop {
+            let distance = self.distance(&store.nodes[node].pivot, chars)?;
+            if distance == 0 {
+                store.nodes[node].entries.retain(|&i| i != index);
+                return Ok(());
+            }
+            match store.nodes[node].children.iter().find(|(d, _)| *d == distance) {
+                Some(&(_, child)) => node = child,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn within(&self, store: &TreeStore, query: &[char], max_distance: u32) -> PyResult<Vec<(usize, u32)>> {
+        let mut found = Vec::new();
+        let mut stack = store.roots.get(&self.root_key(query)).into_iter().copied().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            let distance = self.distance(&store.nodes[node].pivot, query)?;
+            if distance <= max_distance {
+                found.extend(store.nodes[node].entries.iter().map(|&i| (i, distance)));
+            }
+            for &(child_distance, child) in &store.nodes[node].children {
+                if child_distance.saturating_add(max_distance) >= distance
+                    && child_distance <= distance.saturating_add(max_distance)
+                {
+                    stack.push(child);
+                }
+            }
+        }
+        found.sort_unstable_by_key(|&(i, distance)| (distance, i));
+        Ok(found)
+    }
+
+    fn k_nearest(&self, store: &TreeStore, query: &[char], k: usize) -> PyResult<Vec<(usize, u32)>> {
+        // k may be far larger than the tree, and the heap never holds more than one candidate over the count.
+        let mut best: BinaryHeap<(u32, usize)> = BinaryHeap::with_capacity(usize::min(k, store.count) + 1);
+        let mut stack = store.roots.get(&self.root_key(query)).into_iter().copied().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            let distance = self.distance(&store.nodes[node].pivot, query)?;
+            for &i in &store.nodes[node].entries {
+                best.push((distance, i));
+                if best.len() > k {
+                    best.pop();
+                }
+            }
+            let radius = match best.peek() {
+                Some(&(worst, _)) if best.len() == k => worst,
+                _ => u32::MAX,
+            };
+            for &(child_distance, child) in &store.nodes[node].children {
+                if child_distance.saturating_add(radius) >= distance
+                    && child_distance <= distance.saturating_add(radius)
+                {
+                    stack.push(child);
+                }
+            }
+        }
+        let mut found = best.into_iter().map(|(distance, i)| (i, distance)).collect::<Vec<_>>();
+        found.sort_unstable_by_key(|&(i, distance)| (distance, i));
+        Ok(found)
+    }
+
+    fn to_results(store: &TreeStore, found: Vec<(usize, u32)>) -> Vec<(String, f32)> {
+        found
+            .into_iter()
+            .map(|(i, distance)| (store.candidate(i), distance as f32))
+            .collect()
+    }
+}
+
+impl Persist for BKTree {
+    fn write(&self, out: &mut Vec<u8>) {
+        read_lock(&self.store).write(out);
+        self.algorithm.write(out);
+        self.case_sensitive.write(out);
+        self.remove_whitespace.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = RwLock::new(TreeStore::read(input)?);
+        let algorithm: String = Persist::read(input)?;
+        // The scorer is looked up by name on every search, so a name this version does not know is rejected here.
+        if !["LEVENSHTEIN", "DAMERAU", "HAMMING"].contains(&algorithm.as_str()) {
+            return Err(PyValueError::new_err(format!("Corrupt index file: unsupported algorithm {}.", algorithm)));
+        }
+        Ok(BKTree {
+            store,
+            algorithm,
+            case_sensitive: Persist::read(input)?,
+            remove_whitespace: Persist::read(input)?,
+        })
+    }
+}
+
+impl StoredIndex for BKTree {
+    const KIND: u32 = 2;
+    const NAME: &'static str = "BKTree";
+}
+
+#[pymethods]
+impl BKTree {
+    #[new]
+    #[args(algorithm = "\"levenshtein\"", case_sensitive = "false", remove_whitespace = "false")]
+    fn new(
+        candidates: Vec<String>,
+        algorithm: &str,
+        case_sensitive: bool,
+        remove_whitespace: bool,
+    ) -> PyResult<Self> {
+        let algorithm_name = algorithm.to_uppercase();
+        if !["LEVENSHTEIN", "DAMERAU", "HAMMING"].contains(&algorithm_name.as_str()) {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, HAMMING",
+                algorithm_name
+            )));
+        }
+        let tree = BKTree {
+            store: RwLock::new(TreeStore {
+                candidates: Vec::new(),
+                nodes: Vec::new(),
+                roots: HashMap::new(),
+                count: candidates.len(),
+            }),
+            algorithm: algorithm_name,
+            case_sensitive,
+            remove_whitespace,
+        };
+        {
+            let mut store = write_lock(&tree.store);
+            for (index, candidate) in candidates.iter().enumerate() {
+                tree.insert(&mut store, index, char_vec(candidate, case_sensitive, remove_whitespace))?;
+            }
+            store.candidates = candidates.into_iter().map(Some).collect();
+        }
+        Ok(tree)
+    }
+
+    /// search(self, query, max_distance, /)
+    /// --
+    ///
+    /// Find every candidate within max_distance of the query, as (candidate, distance) pairs
+    /// ordered by distance, then by position in the candidates.
+    fn search(&self, py: Python, query: String, max_distance: u32) -> PyResult<Vec<(String, f32)>> {
+        py.allow_threads(|| {
+            install(|| {
+                let processed_query = char_vec(&query, self.case_sensitive, self.remove_whitespace);
+                let store = read_lock(&self.store);
+                Ok(BKTree::to_results(&store, self.within(&store, &processed_query, max_distance)?))
+            })
+        })
+    }
+
+    /// nearest(self, query, k, /)
+    /// --
+    ///
+    /// Find the k candidates closest to the query, as (candidate, distance) pairs ordered by distance,
+    /// then by position in the candidates. Fewer than k pairs are returned when the tree holds fewer comparable candidates.
+    fn nearest(&self, py: Python, query: String, k: usize) -> PyResult<Vec<(String, f32)>> {
+        if k < 1 {
+            return Err(PyValueError::new_err("k must be greater than 0."));
+        }
+        py.allow_threads(|| {
+            install(|| {
+                let processed_query = char_vec(&query, self.case_sensitive, self.remove_whitespace);
+                let store = read_lock(&self.store);
+                Ok(BKTree::to_results(&store, self.k_nearest(&store, &processed_query, k)?))
+            })
+        })
+    }
+
+    /// add(self, candidate, /)
+    /// --
+    ///
+    /// Add a candidate after the existing ones.
+    fn add(&self, candidate: String) -> PyResult<()> {
+        let processed = char_vec(&candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        let index = store.candidates.len();
+        self.insert(&mut store, index, processed)?;
+        store.candidates.push(Some(candidate));
+        store.count += 1;
+        Ok(())
+    }
+
+    /// remove(self, candidate, /)
+    /// --
+    ///
+    /// Remove every copy of the candidate. Raises ValueError when it is not in the tree.
+    fn remove(&self, candidate: &str) -> PyResult<()> {
+        let processed = char_vec(candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, candidate)? {
+            self.detach(&mut store, i, &processed)?;
+            store.candidates[i] = None;
+            store.count -= 1;
+        }
+        Ok(())
+    }
+
+    /// update(self, old, new, /)
+    /// --
+    ///
+    /// Replace every copy of the old candidate with the new one, keeping their positions.
+    /// Raises ValueError when the old candidate is not in the tree.
+    fn update(&self, old: &str, new: String) -> PyResult<()> {
+        let old_processed = char_vec(old, self.case_sensitive, self.remove_whitespace);
+        let new_processed = char_vec(&new, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, old)? {
+            self.detach(&mut store, i, &old_processed)?;
+            self.insert(&mut store, i, new_processed.clone())?;
+            store.candidates[i] = Some(new.clone());
+        }
+        Ok(())
+    }
+
+    #[getter]
+    fn algorithm(&self) -> String {
+        self.algorithm.clone()
+    }
+
+    /// save(self, path, /)
+    /// --
+    ///
+    /// Write the tree to a versioned, checksummed binary file that BKTree.load can read back without rebuilding it.
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_index(self, path)
+    }
+
+    /// load(path, /)
+    /// --
+    ///
+    /// Load a tree written by BKTree.save.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_index(path)
+    }
+
+    fn __len__(&self) -> usize {
+        read_lock(&self.store).count
+    }
+}
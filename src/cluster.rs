@@ -0,0 +1,321 @@
+use crate::finder::{get_layout_scorer, is_distance_algorithm, is_valid_algorithm_name};
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py, Layout};
+use crate::parallel::{install, min_len, min_len_weighted};
+use crate::prefilter::{has_lower_bound, CharBag};
+use crate::utils::char_vec;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+pub struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub fn new(size: usize) -> DisjointSet {
+        DisjointSet { parents: (0..size).collect() }
+    }
+
+    pub fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    pub fn union(&mut self, i: usize, j: usize) {
+        let (root_i, root_j) = (self.find(i), self.find(j));
+        if root_i != root_j {
+            self.parents[usize::max(root_i, root_j)] = usize::min(root_i, root_j);
+        }
+    }
+}
+
+pub fn passes_cutoff(score: f32, cutoff: f32, algorithm: &str) -> bool {
+    if is_distance_algorithm(algorithm) {
+        score <= cutoff
+    } else {
+        score >= cutoff
+    }
+}
+
+pub fn pairwise_matches(
+    processed: &[Vec<char>],
+    algorithm: &str,
+    cutoff: f32,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<(usize, usize, f32)>> {
+    let scorer = &get_layout_scorer(algorithm, layout);
+    let n = processed.len();
+    let bags = if has_lower_bound(algorithm) {
+        processed.par_iter().with_min_len(min_len()).map(|chars| CharBag::new(chars)).collect()
+    } else {
+        Vec::new()
+    };
+    let bags = &bags;
+    // Row i compares against the n - i - 1 strings after it, about n / 2 on average.
+    (0..n)
+        .into_par_iter()
+        .with_min_len(min_len_weighted(n / 2))
+        .flat_map_iter(|i| {
+            (i + 1..n)
+                .filter(move |&j| algorithm != "HAMMING" || processed[i].len() == processed[j].len())
+                .filter(move |&j| bags.is_empty() || !bags[i].exceeds(&bags[j], algorithm, cutoff))
+                .map(move |j| Ok((i, j, scorer(&processed[i], &processed[j], threshold)?)))
+        })
+        .filter(|pair| match pair {
+            Ok((_, _, score)) => passes_cutoff(*score, cutoff, algorithm),
+            Err(_) => true,
+        })
+        .collect()
+}
+
+enum Canonical {
+    First,
+    Longest,
+    Shortest,
+    Central,
+}
+
+impl Canonical {
+    fn from_name(name: &str) -> PyResult<Canonical> {
+        match name.to_uppercase().as_str() {
+            "FIRST" => Ok(Canonical::First),
+            "LONGEST" => Ok(Canonical::Longest),
+            "SHORTEST" => Ok(Canonical::Shortest),
+            "CENTRAL" => Ok(Canonical::Central),
+            canonical_name => Err(PyValueError::new_err(format!(
+                "Unsupported canonical choice: {}. Supported choices are: FIRST, LONGEST, SHORTEST, CENTRAL",
+                canonical_name
+            ))),
+        }
+    }
+
+    fn choose(
+        &self,
+        group: &[usize],
+        strings: &[String],
+        processed: &[Vec<char>],
+        algorithm: &str,
+        threshold: f32,
+        layout: &Layout,
+    ) -> PyResult<usize> {
+        let chosen = match self {
+            Canonical::First => group[0],
+            Canonical::Longest => *group
+                .iter()
+                .rev()
+                .max_by_key(|&&i| strings[i].chars().count())
+                .expect("Duplicate groups are never empty."),
+            Canonical::Shortest => *group
+                .iter()
+                .min_by_key(|&&i| strings[i].chars().count())
+                .expect("Duplicate groups are never empty."),
+            Canonical::Central => {
+                let scorer = get_layout_scorer(algorithm, layout);
+                let mut best = group[0];
+                let mut best_total = None;
+                for &i in group {
+                    let total = group
+                        .iter()
+                        .filter(|&&j| j != i)
+                        .map(|&j| scorer(&processed[i], &processed[j], threshold))
+                        .sum::<PyResult<f32>>()?;
+                    let improves = match best_total {
+                        None => true,
+                        Some(best_total) => total != best_total && passes_cutoff(total, best_total, algorithm),
+                    };
+                    if improves {
+                        best = i;
+                        best_total = Some(total);
+                    }
+                }
+                best
+            }
+        };
+        Ok(chosen)
+    }
+}
+
+/// dedupe(strings, /, algorithm='levenshtein', cutoff=1.0, canonical='first', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Group near-duplicate strings. Two strings are duplicates when their score is within the cutoff
+/// (at most the cutoff for distances, at least the cutoff for similarities), and groups are formed
+/// from chains of duplicates. Returns a (canonical, members) pair for every group of two or more strings.
+/// With algorithm='typo', layout is the keyboard layout to measure typos on, as in typo_distance.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    cutoff = "1.0",
+    canonical = "\"first\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn dedupe(
+    py: Python,
+    strings: Vec<String>,
+    algorithm: &str,
+    cutoff: f32,
+    canonical: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<(String, Vec<String>)>> {
+    let algorithm_name = algorithm.to_uppercase();
+    if !is_valid_algorithm_name(&algorithm_name) {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+            algorithm_name
+        )));
+    }
+    let canonical = Canonical::from_name(canonical)?;
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            let processed = strings
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|string| char_vec(string, case_sensitive, remove_whitespace))
+                .collect::<Vec<_>>();
+            let mut components = DisjointSet::new(strings.len());
+            for (i, j, _) in pairwise_matches(&processed, &algorithm_name, cutoff, threshold, layout)? {
+                components.union(i, j);
+            }
+            let mut groups: Vec<Vec<usize>> = vec![Vec::new(); strings.len()];
+            for i in 0..strings.len() {
+                let root = components.find(i);
+                groups[root].push(i);
+            }
+            groups
+                .into_iter()
+                .filter(|group| group.len() > 1)
+                .map(|group| {
+                    let canonical_index =
+                        canonical.choose(&group, &strings, &processed, &algorithm_name, threshold, layout)?;
+                    Ok((
+                        strings[canonical_index].clone(),
+                        group.iter().map(|&i| strings[i].clone()).collect(),
+                    ))
+                })
+                .collect()
+        })
+    })
+}
+
+fn leader_clusters(
+    processed: &[Vec<char>],
+    algorithm: &str,
+    cutoff: f32,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<usize>> {
+    let scorer = get_layout_scorer(algorithm, layout);
+    let mut leaders: Vec<usize> = Vec::new();
+    let mut labels = Vec::with_capacity(processed.len());
+    for (i, chars) in processed.iter().enumerate() {
+        let scores = leaders
+            .par_iter()
+            .with_min_len(min_len())
+            .enumerate()
+            .filter(|(_, &leader)| algorithm != "HAMMING" || processed[leader].len() == chars.len())
+            .map(|(label, &leader)| Ok((label, scorer(chars, &processed[leader], threshold)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        let mut best: Option<(usize, f32)> = None;
+        for (label, score) in scores {
+            if !passes_cutoff(score, cutoff, algorithm) {
+                continue;
+            }
+            best = match best {
+                Some((_, best_score)) if score == best_score || passes_cutoff(best_score, score, algorithm) => best,
+                _ => Some((label, score)),
+            };
+        }
+        match best {
+            Some((label, _)) => labels.push(label),
+            None => {
+                labels.push(leaders.len());
+                leaders.push(i);
+            }
+        }
+    }
+    Ok(labels)
+}
+
+/// cluster(strings, /, algorithm='levenshtein', cutoff=1.0, method='connected', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Cluster strings by similarity and return a cluster id for every input, numbered in order of first appearance.
+/// "connected" (or "single") clusters are the connected components of the graph linking strings within the cutoff,
+/// which is single-linkage clustering cut at the cutoff. "leader" assigns each string in turn to the closest
+/// existing cluster leader within the cutoff, or makes it the leader of a new cluster.
+/// With algorithm='typo', layout is the keyboard layout to measure typos on, as in typo_distance.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    cutoff = "1.0",
+    method = "\"connected\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn cluster(
+    py: Python,
+    strings: Vec<String>,
+    algorithm: &str,
+    cutoff: f32,
+    method: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<usize>> {
+    let algorithm_name = algorithm.to_uppercase();
+    if !is_valid_algorithm_name(&algorithm_name) {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+            algorithm_name
+        )));
+    }
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            let processed = strings
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|string| char_vec(string, case_sensitive, remove_whitespace))
+                .collect::<Vec<_>>();
+            match method.to_uppercase().as_str() {
+                "CONNECTED" | "SINGLE" => {
+                    let mut components = DisjointSet::new(strings.len());
+                    for (i, j, _) in pairwise_matches(&processed, &algorithm_name, cutoff, threshold, layout)? {
+                        components.union(i, j);
+                    }
+                    let mut root_labels = vec![None; strings.len()];
+                    let mut next_label = 0;
+                    Ok((0..strings.len())
+                        .map(|i| {
+                            let root = components.find(i);
+                            *root_labels[root].get_or_insert_with(|| {
+                                next_label += 1;
+                                next_label - 1
+                            })
+                        })
+                        .collect())
+                }
+                "LEADER" => leader_clusters(&processed, &algorithm_name, cutoff, threshold, layout),
+                method_name => Err(PyValueError::new_err(format!(
+                    "Unsupported clustering method: {}. Supported methods are: CONNECTED, SINGLE, LEADER",
+                    method_name
+                ))),
+            }
+        })
+    })
+}
@@ -0,0 +1,497 @@
+use crate::internal_scorer::levenshtein_row;
+use crate::utils::char_vec;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditKind {
+    Replace,
+    Insert,
+    Delete,
+}
+
+impl EditKind {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EditKind::Replace => "replace",
+            EditKind::Insert => "insert",
+            EditKind::Delete => "delete",
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> PyResult<EditKind> {
+        match tag {
+            "replace" => Ok(EditKind::Replace),
+            "insert" => Ok(EditKind::Insert),
+            "delete" => Ok(EditKind::Delete),
+            _ => Err(PyValueError::new_err(format!(
+                "Unsupported edit operation: {}. Supported operations are: replace, insert, delete",
+                tag
+            ))),
+        }
+    }
+}
+
+pub type Opcode = (&'static str, usize, usize, usize, usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EditOp {
+    pub kind: EditKind,
+    pub src_pos: usize,
+    pub dest_pos: usize,
+}
+
+impl EditOp {
+    pub fn inverse(&self) -> EditOp {
+        let kind = match self.kind {
+            EditKind::Replace => EditKind::Replace,
+            EditKind::Insert => EditKind::Delete,
+            EditKind::Delete => EditKind::Insert,
+        };
+        EditOp { kind, src_pos: self.dest_pos, dest_pos: self.src_pos }
+    }
+}
+
+fn parse_editops(ops: Vec<(String, usize, usize)>) -> PyResult<Vec<EditOp>> {
+    ops.into_iter()
+        .map(|(tag, src_pos, dest_pos)| {
+            Ok(EditOp { kind: EditKind::from_tag(&tag)?, src_pos, dest_pos })
+        })
+        .collect()
+}
+
+/// The characters of a word, the characters char_vec(word, case_sensitive, remove_whitespace) turns them into
+/// and, for each of those, the index of the character it came from. Lowercasing can turn one character into
+/// several and removed whitespace into none, so edit operation positions have to be mapped back through this.
+fn char_origins(word: &str, case_sensitive: bool, remove_whitespace: bool) -> (Vec<char>, Vec<char>, Vec<usize>) {
+    let chars = word.chars().collect::<Vec<_>>();
+    let mut processed = Vec::with_capacity(chars.len());
+    let mut origins = Vec::with_capacity(chars.len());
+    for (i, chr) in chars.iter().enumerate() {
+        let lowered = if case_sensitive { vec![*chr] } else { chr.to_lowercase().collect() };
+        for processed_chr in lowered {
+            if !remove_whitespace || !processed_chr.is_whitespace() {
+                processed.push(processed_chr);
+                origins.push(i);
+            }
+        }
+    }
+    (chars, processed, origins)
+}
+
+fn push_dest(result: &mut String, last_dest: &mut Option<usize>, dest: &[char], dest_origins: &[usize], dest_pos: usize) {
+    // A dest character that was processed into several is written once.
+    let dest_index = dest_origins[dest_pos];
+    if *last_dest != Some(dest_index) {
+        result.push(dest[dest_index]);
+        *last_dest = Some(dest_index);
+    }
+}
+
+/// Apply edit operations, whose positions index the processed characters of source and dest, to the original
+/// characters of source. Characters none of whose processed characters are edited are copied as they are,
+/// and a character that is only partly edited keeps the rest of its processed characters.
+pub fn apply_edits(
+    source: &[char],
+    source_processed: &[char],
+    source_origins: &[usize],
+    dest: &[char],
+    dest_origins: &[usize],
+    ops: &[EditOp],
+) -> PyResult<String> {
+    let mut src_pos = 0;
+    let mut dest_pos = 0;
+    for op in ops {
+        if op.src_pos < src_pos || op.dest_pos < dest_pos {
+            return Err(PyValueError::new_err(
+                "Edit operations must be sorted by source and destination position.",
+            ));
+        }
+        let consumes_src = op.kind != EditKind::Insert;
+        let consumes_dest = op.kind != EditKind::Delete;
+        if op.src_pos + consumes_src as usize > source_processed.len()
+            || op.dest_pos + consumes_dest as usize > dest_origins.len()
+        {
+            return Err(PyValueError::new_err(format!(
+                "Edit operation ({}, {}, {}) is out of range.",
+                op.kind.tag(),
+                op.src_pos,
+                op.dest_pos
+            )));
+        }
+        src_pos = op.src_pos + consumes_src as usize;
+        dest_pos = op.dest_pos + consumes_dest as usize;
+    }
+
+    let mut result = String::with_capacity(dest.len());
+    let mut last_dest = None;
+    let mut ops = ops.iter().peekable();
+    let mut end = 0;
+    for (i, chr) in source.iter().enumerate() {
+        let start = end;
+        while end < source_origins.len() && source_origins[end] == i {
+            end += 1;
+        }
+        let edited = ops
+            .clone()
+            .take_while(|op| op.src_pos < end)
+            .any(|op| op.kind != EditKind::Insert || op.src_pos > start);
+        if start == end || !edited {
+            // Insertions before a removed whitespace character go after it, in front of the next kept one.
+            if start < end {
+                while let Some(op) = ops.next_if(|op| op.src_pos == start) {
+                    push_dest(&mut result, &mut last_dest, dest, dest_origins, op.dest_pos);
+                }
+            }
+            result.push(*chr);
+            continue;
+        }
+        for (pos, processed_chr) in (start..end).zip(&source_processed[start..end]) {
+            while let Some(op) = ops.next_if(|op| op.src_pos == pos && op.kind == EditKind::Insert) {
+                push_dest(&mut result, &mut last_dest, dest, dest_origins, op.dest_pos);
+            }
+            match ops.next_if(|op| op.src_pos == pos) {
+                Some(op) if op.kind == EditKind::Replace => {
+                    push_dest(&mut result, &mut last_dest, dest, dest_origins, op.dest_pos)
+                }
+                Some(_) => {}
+                None => result.push(*processed_chr),
+            }
+        }
+    }
+    for op in ops {
+        push_dest(&mut result, &mut last_dest, dest, dest_origins, op.dest_pos);
+    }
+    Ok(result)
+}
+
+/// Every row of the Levenshtein matrix, which the traceback in levenshtein_editops walks back through.
+/// The scorers only keep the last row, so the rows come from the same in-place recurrence, copied as it goes.
+pub fn levenshtein_matrix(word1_chars: &[char], word2_chars: &[char]) -> Vec<Vec<usize>> {
+    let mut row: Vec<usize> = (0..=word2_chars.len()).collect();
+    let mut d = Vec::with_capacity(word1_chars.len() + 1);
+    d.push(row.clone());
+    for &src_chr in word1_chars {
+        levenshtein_row(&mut row, src_chr, word2_chars);
+        d.push(row.clone());
+    }
+    d
+}
+
+pub fn levenshtein_editops(word1_chars: &[char], word2_chars: &[char]) -> Vec<EditOp> {
+    let d = levenshtein_matrix(word1_chars, word2_chars);
+    let mut i = word1_chars.len();
+    let mut j = word2_chars.len();
+    let mut ops = Vec::with_capacity(d[i][j]);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && word1_chars[i - 1] == word2_chars[j - 1] && d[i][j] == d[i - 1][j - 1] {
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && j > 0 && d[i][j] == d[i - 1][j - 1] + 1 {
+            i -= 1;
+            j -= 1;
+            ops.push(EditOp { kind: EditKind::Replace, src_pos: i, dest_pos: j });
+        } else if i > 0 && d[i][j] == d[i - 1][j] + 1 {
+            i -= 1;
+            ops.push(EditOp { kind: EditKind::Delete, src_pos: i, dest_pos: j });
+        } else {
+            j -= 1;
+            ops.push(EditOp { kind: EditKind::Insert, src_pos: i, dest_pos: j });
+        }
+    }
+    ops.reverse();
+    ops
+}
+
+pub fn lcs_matrix(word1_chars: &[char], word2_chars: &[char]) -> Vec<Vec<usize>> {
+    let n = word1_chars.len();
+    let m = word2_chars.len();
+    let mut d = vec![vec![0; m + 1]; n + 1];
+    for i in 1..=n {
+        let src_chr = word1_chars[i - 1];
+        for j in 1..=m {
+            d[i][j] = if src_chr == word2_chars[j - 1] {
+                d[i - 1][j - 1] + 1
+            } else {
+                usize::max(d[i - 1][j], d[i][j - 1])
+            };
+        }
+    }
+    d
+}
+
+pub fn lcs_editops(word1_chars: &[char], word2_chars: &[char]) -> Vec<EditOp> {
+    let d = lcs_matrix(word1_chars, word2_chars);
+    let mut i = word1_chars.len();
+    let mut j = word2_chars.len();
+    let mut ops = Vec::with_capacity(i + j - 2 * d[i][j]);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && word1_chars[i - 1] == word2_chars[j - 1] && d[i][j] == d[i - 1][j - 1] + 1 {
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && (j == 0 || d[i - 1][j] >= d[i][j - 1]) {
+            i -= 1;
+            ops.push(EditOp { kind: EditKind::Delete, src_pos: i, dest_pos: j });
+        } else {
+            j -= 1;
+            ops.push(EditOp { kind: EditKind::Insert, src_pos: i, dest_pos: j });
+        }
+    }
+    ops.reverse();
+    ops
+}
+
+// LCS aligns without substitutions. It only exists for the alignment functions and is not a scoring
+// algorithm, so the finders do not accept it.
+fn get_editops(
+    algorithm: &str,
+    word1_chars: &[char],
+    word2_chars: &[char],
+) -> PyResult<Vec<EditOp>> {
+    match algorithm.to_uppercase().as_str() {
+        "LEVENSHTEIN" => Ok(levenshtein_editops(word1_chars, word2_chars)),
+        "LCS" => Ok(lcs_editops(word1_chars, word2_chars)),
+        algorithm_name => Err(PyValueError::new_err(format!(
+            "Unsupported alignment algorithm: {}. Supported alignment algorithms are: LEVENSHTEIN, LCS",
+            algorithm_name
+        ))),
+    }
+}
+
+fn display_chars(word: &str, processed: &[char], remove_whitespace: bool) -> Vec<char> {
+    let original = char_vec(word, true, remove_whitespace);
+    if original.len() == processed.len() {
+        original
+    } else {
+        processed.to_vec()
+    }
+}
+
+pub fn editops_to_opcodes(
+    ops: &[EditOp],
+    src_len: usize,
+    dest_len: usize,
+) -> Vec<Opcode> {
+    let mut opcodes = Vec::new();
+    let mut src_pos = 0;
+    let mut dest_pos = 0;
+    let mut k = 0;
+    while k < ops.len() {
+        let op = ops[k];
+        if op.src_pos > src_pos || op.dest_pos > dest_pos {
+            opcodes.push(("equal", src_pos, op.src_pos, dest_pos, op.dest_pos));
+            src_pos = op.src_pos;
+            dest_pos = op.dest_pos;
+        }
+        let (src_start, dest_start) = (src_pos, dest_pos);
+        while k < ops.len()
+            && ops[k].kind == op.kind
+            && ops[k].src_pos == src_pos
+            && ops[k].dest_pos == dest_pos
+        {
+            match op.kind {
+                EditKind::Replace => {
+                    src_pos += 1;
+                    dest_pos += 1;
+                }
+                EditKind::Delete => src_pos += 1,
+                EditKind::Insert => dest_pos += 1,
+            }
+            k += 1;
+        }
+        opcodes.push((op.kind.tag(), src_start, src_pos, dest_start, dest_pos));
+    }
+    if src_pos < src_len || dest_pos < dest_len {
+        opcodes.push(("equal", src_pos, src_len, dest_pos, dest_len));
+    }
+    opcodes
+}
+
+/// editops(a, b, /, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// List the edit operations that turn a into b as (tag, src_pos, dest_pos) tuples,
+/// where tag is one of "replace", "insert" or "delete".
+#[pyfunction(
+    case_sensitive = "false",
+    remove_whitespace = "false"
+)]
+pub fn editops(
+    word1: &str,
+    word2: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool
+) -> PyResult<Vec<(&'static str, usize, usize)>> {
+    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
+    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
+    Ok(levenshtein_editops(&word1_chars, &word2_chars)
+        .iter()
+        .map(|op| (op.kind.tag(), op.src_pos, op.dest_pos))
+        .collect())
+}
+
+/// opcodes(a, b, /, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// List the difflib-style (tag, i1, i2, j1, j2) opcodes that turn a into b,
+/// where tag is one of "equal", "replace", "insert" or "delete".
+#[pyfunction(
+    case_sensitive = "false",
+    remove_whitespace = "false"
+)]
+pub fn opcodes(
+    word1: &str,
+    word2: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool
+) -> PyResult<Vec<Opcode>> {
+    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
+    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
+    let ops = levenshtein_editops(&word1_chars, &word2_chars);
+    Ok(editops_to_opcodes(&ops, word1_chars.len(), word2_chars.len()))
+}
+
+/// matching_blocks(a, b, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// List the (i, j, size) blocks where a[i:i+size] == b[j:j+size] in the alignment of a and b,
+/// terminated by a (len(a), len(b), 0) block like difflib. algorithm is "levenshtein" or "lcs",
+/// an alignment without substitutions that only the alignment functions accept.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false"
+)]
+pub fn matching_blocks(
+    word1: &str,
+    word2: &str,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool
+) -> PyResult<Vec<(usize, usize, usize)>> {
+    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
+    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
+    let ops = get_editops(algorithm, &word1_chars, &word2_chars)?;
+    let mut blocks = editops_to_opcodes(&ops, word1_chars.len(), word2_chars.len())
+        .into_iter()
+        .filter(|opcode| opcode.0 == "equal")
+        .map(|(_, i1, i2, j1, _)| (i1, j1, i2 - i1))
+        .collect::<Vec<_>>();
+    blocks.push((word1_chars.len(), word2_chars.len(), 0));
+    Ok(blocks)
+}
+
+/// alignment(a, b, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, gap='-')
+/// --
+///
+/// Align a and b, returning the aligned a, a match line and the aligned b.
+/// The match line marks equal characters with "|", substitutions with "." and gaps with " ".
+/// algorithm is "levenshtein" or "lcs", an alignment without substitutions that only the alignment functions accept.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    gap = "'-'"
+)]
+pub fn alignment(
+    word1: &str,
+    word2: &str,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    gap: char,
+) -> PyResult<(String, String, String)> {
+    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
+    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
+    let ops = get_editops(algorithm, &word1_chars, &word2_chars)?;
+    let word1_display = display_chars(word1, &word1_chars, remove_whitespace);
+    let word2_display = display_chars(word2, &word2_chars, remove_whitespace);
+    let mut aligned1 = String::new();
+    let mut matches = String::new();
+    let mut aligned2 = String::new();
+    for (tag, i1, i2, j1, j2) in editops_to_opcodes(&ops, word1_chars.len(), word2_chars.len()) {
+        match tag {
+            "equal" | "replace" => {
+                let marker = if tag == "equal" { '|' } else { '.' };
+                for k in 0..i2 - i1 {
+                    aligned1.push(word1_display[i1 + k]);
+                    matches.push(marker);
+                    aligned2.push(word2_display[j1 + k]);
+                }
+            }
+            "delete" => {
+                for chr in &word1_display[i1..i2] {
+                    aligned1.push(*chr);
+                    matches.push(' ');
+                    aligned2.push(gap);
+                }
+            }
+            _ => {
+                for chr in &word2_display[j1..j2] {
+                    aligned1.push(gap);
+                    matches.push(' ');
+                    aligned2.push(*chr);
+                }
+            }
+        }
+    }
+    Ok((aligned1, matches, aligned2))
+}
+
+/// format_alignment(a, b, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, gap='-')
+/// --
+///
+/// Render the alignment of a and b as three lines of text.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    gap = "'-'"
+)]
+pub fn format_alignment(
+    word1: &str,
+    word2: &str,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    gap: char,
+) -> PyResult<String> {
+    let (aligned1, matches, aligned2) =
+        alignment(word1, word2, algorithm, case_sensitive, remove_whitespace, gap)?;
+    Ok(format!("{}\n{}\n{}", aligned1, matches, aligned2))
+}
+
+/// apply_editops(source, ops, dest, /, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// Apply edit operations from editops(source, dest), or any ordered subset of them, to source.
+/// case_sensitive and remove_whitespace must match the ones the operations were made with.
+/// Characters outside the edited positions, including removed whitespace, are copied from source unchanged.
+#[pyfunction(
+    case_sensitive = "false",
+    remove_whitespace = "false"
+)]
+pub fn apply_editops(
+    source: &str,
+    ops: Vec<(String, usize, usize)>,
+    dest: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+) -> PyResult<String> {
+    let (source_chars, source_processed, source_origins) = char_origins(source, case_sensitive, remove_whitespace);
+    let (dest_chars, _, dest_origins) = char_origins(dest, case_sensitive, remove_whitespace);
+    let ops = parse_editops(ops)?;
+    apply_edits(&source_chars, &source_processed, &source_origins, &dest_chars, &dest_origins, &ops)
+}
+
+/// inverse_editops(ops, /)
+/// --
+///
+/// Invert edit operations so that they turn the destination string back into the source.
+#[pyfunction]
+pub fn inverse_editops(ops: Vec<(String, usize, usize)>) -> PyResult<Vec<(&'static str, usize, usize)>> {
+    Ok(parse_editops(ops)?
+        .iter()
+        .map(EditOp::inverse)
+        .map(|op| (op.kind.tag(), op.src_pos, op.dest_pos))
+        .collect())
+}
@@ -1,262 +1,240 @@
 use crate::internal_scorer::*;
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py, Layout};
+use crate::parallel::{install, min_len, min_len_weighted, parallel_threshold};
+use crate::prefilter::CharBag;
+use crate::topk::{BestTies, TopK};
 use crate::utils::char_vec;
-use ordered_float::OrderedFloat;
-use pyo3::{exceptions::PyValueError, prelude::*};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
 use rayon::prelude::*;
 
-/// closest(target, candidates, /, algorithm='levenshtein', case_sensitive=False)
+/// closest(target, candidates, /, algorithm='levenshtein', case_sensitive=False, tie_break='original', layout=None)
 /// --
 ///
-/// Find the closest match to the target string in the candidates.
+/// Find the closest match to the target string in the candidates. Equal scores go to the candidate that comes
+/// first, or with tie_break='shortest' or 'lexicographic' to the shortest or alphabetically first one.
+/// With algorithm='typo', layout is the keyboard layout to measure typos on, as in typo_distance.
 #[pyfunction(
     algorithm = "\"levenshtein\"",
     case_sensitive = "false",
     remove_whitespace = "false",
-    threshold = "0.0"
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn closest(
-    target: &str,
-    options: Vec<&str>,
+    py: Python,
+    target: String,
+    options: Vec<String>,
     algorithm: &str,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
 ) -> PyResult<String> {
-    let algorithm_name = algorithm.to_uppercase();
-    if options.len() == 0 {
-        return Err(PyValueError::new_err("No options provided."));
-    }
-    if !is_valid_algorithm_name(&algorithm_name) {
-        return Err(PyValueError::new_err(format!(
-            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, JARO, JAROWINKLER, HAMMING",
-            algorithm_name
-        )));
-    }
-    let scorer = get_scorer(&algorithm_name);
-    if algorithm_name == "HAMMING" {
-        for option in &options {
-            if option.len() != target.len() {
-                return Err(PyValueError::new_err(
-                    "Words must be the same length to use Hamming distance.",
-                ));
-            }
-        }
-    }
-    let closest_option;
-    let processed_target = char_vec(target, case_sensitive, remove_whitespace);
-    if algorithm_name == "LEVENSHTEIN"
-        || algorithm_name == "HAMMING"
-    {
-        closest_option = options
-            .into_par_iter()
-            .min_by_key(|option| {
-                OrderedFloat(
-                    scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                        format!(
-                            "Could not calcuate score with algorithm {} between {} and {}",
-                            algorithm_name, target, option
-                        )
-                        .as_str(),
-                    ),
-                )
-            })
-            .expect("No scored values were present.")
-            .to_string();
-    } else {
-        closest_option = options
-            .into_par_iter()
-            .max_by_key(|option| {
-                OrderedFloat(
-                    scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                        format!(
-                            "Could not calcuate score with algorithm {} between {} and {}",
-                            algorithm_name, target, option
-                        )
-                        .as_str(),
-                    ),
-                )
-            })
-            .expect("No scored values were present.")
-            .to_string();
-    }
-    Ok(closest_option)
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            Ok(n_best_options(&target, &options, 1, algorithm, case_sensitive, remove_whitespace, threshold, tie_break, layout)?
+                .remove(0)
+                .0)
+        })
+    })
 }
 
 #[pyfunction(
     algorithm = "\"levenshtein\"",
     case_sensitive = "false",
     remove_whitespace = "false",
-    threshold = "0.0"
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn closest_with_score(
-    target: &str,
-    options: Vec<&str>,
+    py: Python,
+    target: String,
+    options: Vec<String>,
     algorithm: &str,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
 ) -> PyResult<(String, f32)> {
-    let algorithm_name = algorithm.to_uppercase();
-    if options.len() == 0 {
-        return Err(PyValueError::new_err("No options provided."));
-    }
-    if !is_valid_algorithm_name(&algorithm_name) {
-        return Err(PyValueError::new_err(format!(
-            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, JARO, JAROWINKLER, HAMMING",
-            algorithm_name
-        )));
-    }
-    let scorer = get_scorer(&algorithm_name);
-    if algorithm_name == "HAMMING" {
-        for option in &options {
-            if option.len() != target.len() {
-                return Err(PyValueError::new_err(
-                    "Words must be the same length to use Hamming distance.",
-                ));
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            Ok(n_best_options(&target, &options, 1, algorithm, case_sensitive, remove_whitespace, threshold, tie_break, layout)?
+                .remove(0))
+        })
+    })
+}
+
+/// closest_all(target, candidates, /, algorithm='levenshtein', case_sensitive=False, tie_break='original', layout=None)
+/// --
+///
+/// Find every candidate that shares the best score for the target string, rather than picking one of them.
+/// They keep the candidates' order, or with tie_break='shortest' or 'lexicographic' come shortest or alphabetically first.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn closest_all(
+    py: Python,
+    target: String,
+    options: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<String>> {
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            let algorithm_name = algorithm.to_uppercase();
+            if options.is_empty() {
+                return Err(PyValueError::new_err("No options provided."));
             }
-        }
-    }
-    let closest_option;
-    let processed_target = char_vec(target, case_sensitive, remove_whitespace);
-    if algorithm_name == "LEVENSHTEIN"
-        || algorithm_name == "HAMMING"
-    {
-        closest_option = options
-            .into_par_iter()
-            .min_by_key(|option| {
-                OrderedFloat(
-                    scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                        format!(
-                            "Could not calcuate score with algorithm {} between {} and {}",
-                            algorithm_name, target, option
-                        )
-                        .as_str(),
-                    ),
-                )
-            })
-            .expect("No scored values were present.")
-            .to_string();
-    } else {
-        closest_option = options
-            .into_par_iter()
-            .max_by_key(|option| {
-                OrderedFloat(
-                    scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                        format!(
-                            "Could not calcuate score with algorithm {} between {} and {}",
-                            algorithm_name, target, option
-                        )
-                        .as_str(),
-                    ),
-                )
-            })
-            .expect("No scored values were present.")
-            .to_string();
-    }
-    Ok((closest_option.clone(), scorer(
-        closest_option.as_str(),
-        &processed_target,
-        case_sensitive,
-        remove_whitespace,
-        threshold,
-    ).expect(
-        format!(
-            "Could not calcuate score with algorithm {} between {} and {}",
-            algorithm_name, target, closest_option
-        )
-        .as_str(),
-    )))
+            if !is_valid_algorithm_name(&algorithm_name) {
+                return Err(PyValueError::new_err(format!(
+                    "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+                    algorithm_name
+                )));
+            }
+            check_threshold(&algorithm_name, threshold)?;
+            let tie_break = TieBreak::from_name(tie_break)?;
+            let (processed_target, processed_options) =
+                process_options(&target, &options, &algorithm_name, case_sensitive, remove_whitespace)?;
+            let mut best = all_best_processed_options(&processed_target, &processed_options, &algorithm_name, threshold, layout)?
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            match tie_break {
+                TieBreak::Original => {}
+                TieBreak::Shortest => best.sort_by_key(|&i| options[i].chars().count()),
+                TieBreak::Lexicographic => best.sort_by_key(|&i| options[i].as_str()),
+            }
+            Ok(best.into_iter().map(|i| options[i].clone()).collect())
+        })
+    })
 }
 
-/// n_closest(target, candidates, n, /, algorithm='levenshtein', case_sensitive=False)
+/// n_closest(target, candidates, n, /, algorithm='levenshtein', case_sensitive=False, tie_break='original', layout=None)
 /// --
 ///
-/// Find the n closest matches to the target string in the candidates.
+/// Find the n closest matches to the target string in the candidates, best first. Equal scores keep the
+/// candidates' order, or with tie_break='shortest' or 'lexicographic' put shorter or alphabetically earlier ones first.
 #[pyfunction(
     algorithm = "\"levenshtein\"",
     case_sensitive = "false",
     remove_whitespace = "false",
-    threshold = "0.0"
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn n_closest(
-    target: &str,
-    options: Vec<&str>,
+    py: Python,
+    target: String,
+    options: Vec<String>,
     n: usize,
     algorithm: &str,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
 ) -> PyResult<Vec<String>> {
-    let algorithm_name = algorithm.to_uppercase();
-    if options.len() == 0 {
-        return Err(PyValueError::new_err("No options provided."));
-    }
-    if n < 1 {
-        return Err(PyValueError::new_err("n must be greater than 0."));
-    }
-    else if n > options.len() {
-        return Err(PyValueError::new_err(format!(
-            "n must be less than or equal to the number of options: {}",
-            options.len()
-        )));
-    }
-    if !is_valid_algorithm_name(&algorithm_name) {
-        return Err(PyValueError::new_err(format!(
-            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, JARO, JAROWINKLER, HAMMING",
-            algorithm_name
-        )));
-    }
-    let scorer = get_scorer(&algorithm_name);
-    if algorithm_name == "HAMMING" {
-        for option in &options {
-            if option.len() != target.len() {
-                return Err(PyValueError::new_err(
-                    "Words must be the same length to use Hamming distance.",
-                ));
-            }
-        }
-    }
-    let processed_target = char_vec(target, case_sensitive, remove_whitespace);
-    let mut scores = options
-        .par_iter()
-        .map(|option| {
-            (
-                option,
-                scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                    format!(
-                        "Could not calcuate score with algorithm {} between {} and {}",
-                        algorithm_name, target, option
-                    )
-                    .as_str(),
-                ),
-            )
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            Ok(n_best_options(&target, &options, n, algorithm, case_sensitive, remove_whitespace, threshold, tie_break, layout)?
+                .into_iter()
+                .map(|(option, _)| option)
+                .collect())
         })
-        .collect::<Vec<_>>();
-    sort_scores(&mut scores, &algorithm_name);
-    let mut best = Vec::with_capacity(n);
-    for (option, _) in scores.iter().take(n) {
-        best.push(String::from(**option));
-    }
-    return Ok(best);
+    })
 }
 
 #[pyfunction(
     algorithm = "\"levenshtein\"",
     case_sensitive = "false",
     remove_whitespace = "false",
-    threshold = "0.0"
+    threshold = "0.0",
+    tie_break = "\"original\"",
+    layout = "None"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn n_closest_with_score(
+    py: Python,
+    target: String,
+    options: Vec<String>,
+    n: usize,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    tie_break: &str,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<(String, f32)>> {
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            n_best_options(&target, &options, n, algorithm, case_sensitive, remove_whitespace, threshold, tie_break, layout)
+        })
+    })
+}
+
+/// How options with equal scores are ordered.
+pub enum TieBreak {
+    Original,
+    Shortest,
+    Lexicographic,
+}
+
+impl TieBreak {
+    pub fn from_name(name: &str) -> PyResult<TieBreak> {
+        match name.to_uppercase().as_str() {
+            "ORIGINAL" => Ok(TieBreak::Original),
+            "SHORTEST" => Ok(TieBreak::Shortest),
+            "LEXICOGRAPHIC" => Ok(TieBreak::Lexicographic),
+            tie_break_name => Err(PyValueError::new_err(format!(
+                "Unsupported tie break: {}. Supported tie breaks are: ORIGINAL, SHORTEST, LEXICOGRAPHIC",
+                tie_break_name
+            ))),
+        }
+    }
+}
+
+/// The n best options for the target with their scores, best first and ties ordered by tie_break.
+#[allow(clippy::too_many_arguments)]
+pub fn n_best_options(
     target: &str,
-    options: Vec<&str>,
+    options: &[String],
     n: usize,
     algorithm: &str,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
+    tie_break: &str,
+    layout: &Layout,
 ) -> PyResult<Vec<(String, f32)>> {
     let algorithm_name = algorithm.to_uppercase();
-    if options.len() == 0 {
+    if options.is_empty() {
         return Err(PyValueError::new_err("No options provided."));
     }
     if n < 1 {
@@ -270,13 +248,50 @@ pub fn n_closest_with_score(
     }
     if !is_valid_algorithm_name(&algorithm_name) {
         return Err(PyValueError::new_err(format!(
-            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, JARO, JAROWINKLER, HAMMING",
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
             algorithm_name
         )));
     }
-    let scorer = get_scorer(&algorithm_name);
+    check_threshold(&algorithm_name, threshold)?;
+    let tie_break = TieBreak::from_name(tie_break)?;
+    let (processed_target, processed_options) =
+        process_options(target, options, &algorithm_name, case_sensitive, remove_whitespace)?;
+    let best = match tie_break {
+        TieBreak::Original => {
+            n_best_processed_options(&processed_target, &processed_options, n, &algorithm_name, threshold, layout)?
+        }
+        TieBreak::Shortest => n_best_processed_options_by(
+            &processed_target,
+            &processed_options,
+            n,
+            &algorithm_name,
+            threshold,
+            layout,
+            |i| options[i].chars().count(),
+        )?,
+        TieBreak::Lexicographic => n_best_processed_options_by(
+            &processed_target,
+            &processed_options,
+            n,
+            &algorithm_name,
+            threshold,
+            layout,
+            |i| options[i].as_str(),
+        )?,
+    };
+    Ok(best.into_iter().map(|(i, score)| (options[i].clone(), score)).collect())
+}
+
+/// Check that Hamming distance applies and preprocess the target and options.
+fn process_options(
+    target: &str,
+    options: &[String],
+    algorithm_name: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+) -> PyResult<(Vec<char>, Vec<Vec<char>>)> {
     if algorithm_name == "HAMMING" {
-        for option in &options {
+        for option in options {
             if option.len() != target.len() {
                 return Err(PyValueError::new_err(
                     "Words must be the same length to use Hamming distance.",
@@ -285,119 +300,531 @@ pub fn n_closest_with_score(
         }
     }
     let processed_target = char_vec(target, case_sensitive, remove_whitespace);
-    let mut scores = options
+    let processed_options = options
         .par_iter()
-        .map(|option| {
-            (
-                option,
-                scorer(option, &processed_target, case_sensitive, remove_whitespace, threshold).expect(
-                    format!(
-                        "Could not calcuate score with algorithm {} between {} and {}",
-                        algorithm_name, target, option
-                    )
-                    .as_str(),
-                ),
-            )
-        })
+        .with_min_len(min_len())
+        .map(|option| char_vec(option, case_sensitive, remove_whitespace))
         .collect::<Vec<_>>();
-    sort_scores(&mut scores, &algorithm_name);
-    let mut best = Vec::with_capacity(n);
-    for (option, score) in scores.iter().take(n) {
-        best.push((String::from(**option), *score));
-    }
-    return Ok(best);
+    Ok((processed_target, processed_options))
 }
 
 #[pyfunction(
     algorithm = "\"levenshtein\"",
     case_sensitive = "false",
     remove_whitespace = "false",
-    threshold = "0.0"
+    threshold = "0.0",
+    layout = "None"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn closest_index_pair(
-    target: &str,
-    text: &str,
+    py: Python,
+    target: String,
+    text: String,
     algorithm: &str,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
+    layout: Option<PyObject>,
 ) -> PyResult<(usize, usize)> {
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || install(move || {
+        let algorithm_name = algorithm.to_uppercase();
+        if text.is_empty() {
+            return Ok((0, 0));
+        }
+        if !is_valid_algorithm_name(&algorithm_name) {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+                algorithm_name
+            )));
+        }
+        check_threshold(&algorithm_name, threshold)?;
+        let scorer = get_layout_scorer(&algorithm_name, layout_or_qwerty(&layout));
+        let processed_target = char_vec(&target, case_sensitive, remove_whitespace);
+        // Windows are taken over characters rather than bytes, so that the indices match Python's and
+        // slicing never splits a multi-byte character.
+        let text_chars = text.chars().collect::<Vec<_>>();
+        let width = target.chars().count();
+        if width > text_chars.len() {
+            return Err(PyValueError::new_err(format!(
+                "Target is longer than the text: {} characters against {}.",
+                width,
+                text_chars.len()
+            )));
+        }
+        let mut scores = (0..text_chars.len() - width + 1)
+            .into_par_iter()
+            .with_min_len(min_len())
+            .map(|i| {
+                let window = text_chars[i..i + width].iter().collect::<String>();
+                let processed_window = char_vec(&window, case_sensitive, remove_whitespace);
+                Ok((i, scorer(&processed_window, &processed_target, threshold)?))
+            })
+            .collect::<PyResult<Vec<(usize, f32)>>>()?;
+        sort_scores(&mut scores, &algorithm_name);
+        Ok((scores[0].0, scores[0].0 + width))
+    }))
+}
+
+/// closest_many(targets, candidates, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Find the closest match in the candidates for each of the targets.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn closest_many(
+    py: Python,
+    targets: Vec<String>,
+    options: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<String>> {
+    Ok(n_closest_many(py, targets, options, 1, algorithm, case_sensitive, remove_whitespace, threshold, layout)?
+        .into_iter()
+        .map(|mut best| best.remove(0))
+        .collect())
+}
+
+/// n_closest_many(targets, candidates, n, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Find the n closest matches in the candidates for each of the targets.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn n_closest_many(
+    py: Python,
+    targets: Vec<String>,
+    options: Vec<String>,
+    n: usize,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<Vec<String>>> {
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || install(move || {
+        let layout = layout_or_qwerty(&layout);
+        let algorithm_name = algorithm.to_uppercase();
+        if options.is_empty() {
+            return Err(PyValueError::new_err("No options provided."));
+        }
+        if n < 1 {
+            return Err(PyValueError::new_err("n must be greater than 0."));
+        }
+        else if n > options.len() {
+            return Err(PyValueError::new_err(format!(
+                "n must be less than or equal to the number of options: {}",
+                options.len()
+            )));
+        }
+        if !is_valid_algorithm_name(&algorithm_name) {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+                algorithm_name
+            )));
+        }
+        check_threshold(&algorithm_name, threshold)?;
+        let processed_targets = targets
+            .par_iter()
+            .with_min_len(min_len())
+            .map(|target| char_vec(target, case_sensitive, remove_whitespace))
+            .collect::<Vec<_>>();
+        let processed_options = options
+            .par_iter()
+            .with_min_len(min_len())
+            .map(|option| char_vec(option, case_sensitive, remove_whitespace))
+            .collect::<Vec<_>>();
+        processed_targets
+            .par_iter()
+            .with_min_len(min_len_weighted(options.len()))
+            .map(|target| {
+                Ok(n_best_processed_options(target, &processed_options, n, &algorithm_name, threshold, layout)?
+                    .iter()
+                    .map(|(i, _)| options[*i].clone())
+                    .collect())
+            })
+            .collect()
+    }))
+}
+
+/// cdist(queries, choices, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Score every query against every choice, returning a len(queries) x len(choices) matrix.
+/// The matrix is a float32 NumPy array when NumPy is installed and a list of lists otherwise.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn cdist(
+    py: Python,
+    queries: Vec<String>,
+    choices: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let layout = optional_layout_from_py(py, layout)?;
+    let scores = py.allow_threads(|| {
+        install(|| {
+            let layout = layout_or_qwerty(&layout);
+            cdist_scores(&queries, &choices, algorithm, case_sensitive, remove_whitespace, threshold, layout)
+        })
+    })?;
+    matrix_to_py(py, scores, queries.len(), choices.len())
+}
+
+pub fn cdist_scores(
+    queries: &[String],
+    choices: &[String],
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<f32>> {
     let algorithm_name = algorithm.to_uppercase();
-    if text.len() == 0 {
-        return Ok((0, 0));
-    }
     if !is_valid_algorithm_name(&algorithm_name) {
         return Err(PyValueError::new_err(format!(
-            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, JARO, JAROWINKLER, HAMMING",
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
             algorithm_name
         )));
     }
-    let scorer = get_scorer(&algorithm_name);
-    let processed_target = char_vec(target, case_sensitive, remove_whitespace);
-    let mut scores: Vec<(usize, f32)> = (0..text.len() - target.len() + 1)
-        .into_par_iter()
-        .map(|i| {
-            (
-                i,
-                scorer(
-                    &text[i..i + target.len()],
-                    &processed_target,
-                    case_sensitive,
-                    remove_whitespace,
-                    threshold,
-                )
-                .expect(
-                    format!(
-                        "Could not calcuate score with algorithm {} between {} and {}",
-                        algorithm_name,
-                        target,
-                        &text[i..i + target.len()]
-                    )
-                    .as_str(),
-                ),
-            )
-        })
+    check_threshold(&algorithm_name, threshold)?;
+    score_matrix(queries, choices, &algorithm_name, case_sensitive, remove_whitespace, threshold, layout)
+}
+
+pub fn score_matrix<S: AsRef<str> + Sync>(
+    queries: &[S],
+    choices: &[S],
+    algorithm_name: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<f32>> {
+    let scorer = &get_layout_scorer(algorithm_name, layout);
+    let processed_queries = queries
+        .par_iter()
+        .with_min_len(min_len())
+        .map(|query| char_vec(query.as_ref(), case_sensitive, remove_whitespace))
+        .collect::<Vec<_>>();
+    let processed_choices = choices
+        .par_iter()
+        .with_min_len(min_len())
+        .map(|choice| char_vec(choice.as_ref(), case_sensitive, remove_whitespace))
         .collect::<Vec<_>>();
-    sort_scores(&mut scores, &algorithm_name);
-    return Ok((scores[0].0, scores[0].0 + target.len()));
+    if algorithm_name == "HAMMING" {
+        for query in &processed_queries {
+            if processed_choices.iter().any(|choice| choice.len() != query.len()) {
+                return Err(PyValueError::new_err(
+                    "Words must be the same length to use Hamming distance.",
+                ));
+            }
+        }
+    }
+    processed_queries
+        .par_iter()
+        .with_min_len(min_len_weighted(processed_choices.len()))
+        .flat_map_iter(|query| {
+            processed_choices
+                .iter()
+                .map(move |choice| scorer(query, choice, threshold))
+        })
+        .collect()
+}
+
+pub fn matrix_to_py(py: Python, scores: Vec<f32>, rows: usize, cols: usize) -> PyResult<PyObject> {
+    match py.import("numpy") {
+        Ok(numpy) => {
+            let bytes = scores
+                .iter()
+                .flat_map(|score| score.to_ne_bytes())
+                .collect::<Vec<u8>>();
+            let array = numpy
+                .call_method1("frombuffer", (PyBytes::new(py, &bytes), numpy.getattr("float32")?))?
+                .call_method1("reshape", ((rows, cols),))?
+                .call_method0("copy")?;
+            Ok(array.into())
+        }
+        Err(_) => {
+            let matrix = if cols == 0 {
+                vec![Vec::new(); rows]
+            } else {
+                scores.chunks(cols).map(|row| row.to_vec()).collect::<Vec<_>>()
+            };
+            Ok(matrix.into_py(py))
+        }
+    }
+}
+
+/// Jaro-Winkler rejects a threshold outside 0.0 to 1.0. Checking it before scoring reports the bad argument
+/// once instead of failing part way through the options.
+pub fn check_threshold(algorithm_name: &str, threshold: f32) -> PyResult<()> {
+    if algorithm_name == "JAROWINKLER" && !(0.0..=1.0).contains(&threshold) {
+        return Err(PyValueError::new_err(format!(
+            "threshold must be between 0.0 and 1.0 for JAROWINKLER, got {}",
+            threshold
+        )));
+    }
+    Ok(())
+}
+
+pub fn is_valid_algorithm_name(algorithm: &str) -> bool {
+    ["LEVENSHTEIN", "DAMERAU", "JARO", "JAROWINKLER", "HAMMING", "TYPO"]
+        .contains(&algorithm)
 }
 
-fn is_valid_algorithm_name(algorithm: &str) -> bool {
-    return ["LEVENSHTEIN", "JARO", "JAROWINKLER", "HAMMING"]
-        .contains(&algorithm);
+pub fn is_distance_algorithm(algorithm: &str) -> bool {
+    ["LEVENSHTEIN", "DAMERAU", "HAMMING", "TYPO"].contains(&algorithm)
 }
 
-fn get_scorer(algorithm: &str) -> fn(&str, &Vec<char>, bool, bool, f32) -> PyResult<f32> {
-    return match algorithm {
-        "JARO" => jaro_similarity_target_preprocessed,
-        "JAROWINKLER" => jaro_winkler_similarity_target_preprocessed,
-        "HAMMING" => hamming_distance_target_preprocessed,
-        "LEVENSHTEIN" => levenshtein_distance_target_preprocessed,
+pub type MatchedScorer = fn(&[char], &[char], f32) -> PyResult<f32>;
+
+pub fn get_matched_scorer(algorithm: &str) -> MatchedScorer {
+    match algorithm {
+        "JARO" => jaro_similarity_target_matched_preprocessed,
+        "JAROWINKLER" => jaro_winkler_similarity_target_matched_preprocessed,
+        "HAMMING" => hamming_distance_target_matched_preprocessed,
+        "LEVENSHTEIN" => levenshtein_distance_target_matched_preprocessed,
+        "DAMERAU" => damerau_levenshtein_distance_target_matched_preprocessed,
+        "TYPO" => typo_distance_target_matched_preprocessed,
         _ => unreachable!(),
+    }
+}
+
+/// The scorer for an algorithm, with typo distance measured on the given keyboard layout.
+pub fn get_layout_scorer<'a>(
+    algorithm: &str,
+    layout: &'a Layout,
+) -> impl Fn(&[char], &[char], f32) -> PyResult<f32> + Sync + 'a {
+    let scorer = get_matched_scorer(algorithm);
+    let typo = algorithm == "TYPO";
+    move |word1_chars: &[char], word2_chars: &[char], threshold: f32| {
+        if typo {
+            Ok(typo_distance_with_layout(word1_chars, word2_chars, layout))
+        } else {
+            scorer(word1_chars, word2_chars, threshold)
+        }
+    }
+}
+
+pub type BoundedScorer = fn(&[char], &[char], f32) -> Option<f32>;
+
+/// Scorers that stop once the distance is certainly over a bound, for the algorithms that have one.
+/// Typo distance depends on the layout, so it is the one that is not a plain function.
+#[allow(clippy::type_complexity)]
+pub fn get_bounded_scorer<'a>(
+    algorithm: &str,
+    layout: &'a Layout,
+) -> Option<impl Fn(&[char], &[char], f32) -> Option<f32> + Sync + 'a> {
+    let bounded_scorer = match algorithm {
+        "HAMMING" => Some(hamming_distance_within as BoundedScorer),
+        "LEVENSHTEIN" => Some(levenshtein_distance_within as BoundedScorer),
+        "DAMERAU" => Some(damerau_levenshtein_distance_within as BoundedScorer),
+        "TYPO" => None,
+        _ => return None,
     };
+    Some(move |word1_chars: &[char], word2_chars: &[char], max_distance: f32| match bounded_scorer {
+        Some(bounded_scorer) => bounded_scorer(word1_chars, word2_chars, max_distance),
+        None => typo_distance_within(word1_chars, word2_chars, layout, max_distance),
+    })
+}
+
+pub fn score_processed_options(
+    target: &[char],
+    processed_options: &[Vec<char>],
+    scorer: &(impl Fn(&[char], &[char], f32) -> PyResult<f32> + Sync),
+    threshold: f32,
+) -> PyResult<Vec<(usize, f32)>> {
+    processed_options
+        .par_iter()
+        .with_min_len(min_len())
+        .enumerate()
+        .map(|(i, option)| Ok((i, scorer(option, target, threshold)?)))
+        .collect()
+}
+
+pub fn n_best_processed_options(
+    target: &[char],
+    processed_options: &[Vec<char>],
+    n: usize,
+    algorithm_name: &str,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<(usize, f32)>> {
+    n_best_processed_options_by(target, processed_options, n, algorithm_name, threshold, layout, |_| ())
+}
+
+/// n_best_processed_options with equal scores ordered by tie_key and then by index.
+pub fn n_best_processed_options_by<T: Ord + Send>(
+    target: &[char],
+    processed_options: &[Vec<char>],
+    n: usize,
+    algorithm_name: &str,
+    threshold: f32,
+    layout: &Layout,
+    tie_key: impl Fn(usize) -> T + Sync,
+) -> PyResult<Vec<(usize, f32)>> {
+    if algorithm_name == "HAMMING" && processed_options.iter().any(|option| option.len() != target.len()) {
+        return Err(PyValueError::new_err(
+            "Words must be the same length to use Hamming distance.",
+        ));
+    }
+    let scorer = get_layout_scorer(algorithm_name, layout);
+    let bounded_scorer = get_bounded_scorer(algorithm_name, layout);
+    let distance = is_distance_algorithm(algorithm_name);
+    let target_bag = CharBag::new(target);
+    // Every thread keeps the n best options it has seen in a heap, and the heaps are merged at the end. Once a heap
+    // is full its worst distance bounds the ones still worth keeping, so options whose length or bag distance is over it
+    // are skipped and the rest are only scored as far as the bound.
+    let best = processed_options
+        .par_iter()
+        .with_min_len(min_len())
+        .enumerate()
+        .try_fold(
+            || TopK::new(n, distance),
+            |mut best, (i, option)| -> PyResult<TopK<T>> {
+                let bound = best.bound();
+                let scored =
+                    score_within(option, target, &target_bag, algorithm_name, &scorer, bounded_scorer.as_ref(), bound, threshold)?;
+                if let Some(score) = scored {
+                    best.push(i, score, tie_key(i));
+                }
+                Ok(best)
+            },
+        )
+        .try_reduce(|| TopK::new(n, distance), |a, b| Ok(a.merge(b)))?;
+    Ok(best.into_sorted())
+}
+
+/// Score an option against the target, or return None when its length or bag distance, or a scorer that stops
+/// early, shows that it is further away than the bound. Similarities have no bound and are always scored.
+#[allow(clippy::too_many_arguments)]
+fn score_within(
+    option: &[char],
+    target: &[char],
+    target_bag: &CharBag,
+    algorithm_name: &str,
+    scorer: &impl Fn(&[char], &[char], f32) -> PyResult<f32>,
+    bounded_scorer: Option<&impl Fn(&[char], &[char], f32) -> Option<f32>>,
+    bound: Option<f32>,
+    threshold: f32,
+) -> PyResult<Option<f32>> {
+    match (bounded_scorer, bound) {
+        (Some(bounded_scorer), Some(bound)) => {
+            if option.len().abs_diff(target.len()) as f32 > bound
+                || target_bag.exceeds(&CharBag::new(option), algorithm_name, bound)
+            {
+                return Ok(None);
+            }
+            Ok(bounded_scorer(option, target, bound))
+        }
+        _ => Ok(Some(scorer(option, target, threshold)?)),
+    }
+}
+
+/// Every option that shares the best score for the target, in their original order. Each thread keeps its best
+/// score so far and the options that have it, so options can be skipped or scored only as far as that score.
+pub fn all_best_processed_options(
+    target: &[char],
+    processed_options: &[Vec<char>],
+    algorithm_name: &str,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<(usize, f32)>> {
+    if algorithm_name == "HAMMING" && processed_options.iter().any(|option| option.len() != target.len()) {
+        return Err(PyValueError::new_err(
+            "Words must be the same length to use Hamming distance.",
+        ));
+    }
+    let scorer = get_layout_scorer(algorithm_name, layout);
+    let bounded_scorer = get_bounded_scorer(algorithm_name, layout);
+    let distance = is_distance_algorithm(algorithm_name);
+    let target_bag = CharBag::new(target);
+    let best = processed_options
+        .par_iter()
+        .with_min_len(min_len())
+        .enumerate()
+        .try_fold(
+            || BestTies::new(distance),
+            |mut best, (i, option)| -> PyResult<BestTies> {
+                let bound = best.bound();
+                let scored =
+                    score_within(option, target, &target_bag, algorithm_name, &scorer, bounded_scorer.as_ref(), bound, threshold)?;
+                if let Some(score) = scored {
+                    best.push(i, score);
+                }
+                Ok(best)
+            },
+        )
+        .try_reduce(|| BestTies::new(distance), |a, b| Ok(a.merge(b)))?;
+    Ok(best.into_sorted())
+}
+
+/// Score the options that may be within max_distance of the target, skipping the ones
+/// whose length or bag distance already puts them further away.
+pub fn score_processed_options_within(
+    target: &[char],
+    processed_options: &[Vec<char>],
+    algorithm_name: &str,
+    max_distance: f32,
+    threshold: f32,
+    layout: &Layout,
+) -> PyResult<Vec<(usize, f32)>> {
+    let scorer = get_layout_scorer(algorithm_name, layout);
+    let target_bag = CharBag::new(target);
+    processed_options
+        .par_iter()
+        .with_min_len(min_len())
+        .enumerate()
+        .filter(|(_, option)| {
+            option.len().abs_diff(target.len()) as f32 <= max_distance
+                && !target_bag.exceeds(&CharBag::new(option), algorithm_name, max_distance)
+        })
+        .map(|(i, option)| Ok((i, scorer(option, target, threshold)?)))
+        .collect()
 }
 
-fn sort_scores<T: Send>(scores: &mut Vec<(T, f32)>, algorithm: &str) {
-    if scores.len() > 1000 {
+/// Sort scores best first. The sort is stable, so equal scores keep their order, and total_cmp gives NaN
+/// a place in it instead of failing the comparison.
+pub fn sort_scores<T: Send>(scores: &mut [(T, f32)], algorithm: &str) {
+    if scores.len() >= parallel_threshold() {
         return par_sort_scores(scores, algorithm);
     }
-    if algorithm == "LEVENSHTEIN"
-        || algorithm == "HAMMING"
-    {
-        scores.sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).expect("Could not compare scores."));
+    if is_distance_algorithm(algorithm) {
+        scores.sort_by(|a, b| a.1.total_cmp(&b.1));
     } else {
-        scores.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).expect("Could not compare scores."));
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
     }
 }
 
-fn par_sort_scores<T: Send>(scores: &mut Vec<(T, f32)>, algorithm: &str) {
-    if algorithm == "LEVENSHTEIN"
-        || algorithm == "HAMMING"
-    {
-        scores
-            .par_sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).expect("Could not compare scores."));
+fn par_sort_scores<T: Send>(scores: &mut [(T, f32)], algorithm: &str) {
+    if is_distance_algorithm(algorithm) {
+        scores.par_sort_by(|a, b| a.1.total_cmp(&b.1));
     } else {
-        scores
-            .par_sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).expect("Could not compare scores."));
+        scores.par_sort_by(|a, b| b.1.total_cmp(&a.1));
     }
 }
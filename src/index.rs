@@ -0,0 +1,303 @@
+use std::sync::RwLock;
+
+use crate::finder::{
+    check_threshold, is_valid_algorithm_name, n_best_processed_options, n_best_processed_options_by, TieBreak,
+};
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py, Layout};
+use crate::parallel::{install, min_len};
+use crate::persist::{corrupt, load_index, save_index, Persist, Reader, StoredIndex};
+use crate::utils::{candidate_positions, char_vec, read_lock, write_lock};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+struct Candidates {
+    candidates: Vec<String>,
+    processed: Vec<Vec<char>>,
+}
+
+/// Index(candidates, /, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// A list of candidates preprocessed once and kept in Rust, to be searched repeatedly
+/// without converting and preprocessing the candidates on every call. Candidates can be added,
+/// removed and updated in place, and queries from other threads wait for an update to finish.
+/// With algorithm='typo', the search methods take the keyboard layout to measure typos on, as in typo_distance.
+/// Ties keep the candidates' order, or with tie_break='shortest' or 'lexicographic' put shorter or alphabetically
+/// earlier ones first.
+#[pyclass]
+pub struct Index {
+    store: RwLock<Candidates>,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+}
+
+impl Index {
+    fn n_best(
+        &self,
+        target: &str,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        tie_break: &str,
+        layout: &Layout,
+    ) -> PyResult<Vec<(String, f32)>> {
+        let algorithm_name = algorithm.to_uppercase();
+        let store = read_lock(&self.store);
+        if store.candidates.is_empty() {
+            return Err(PyValueError::new_err("No options provided."));
+        }
+        if n < 1 {
+            return Err(PyValueError::new_err("n must be greater than 0."));
+        }
+        else if n > store.candidates.len() {
+            return Err(PyValueError::new_err(format!(
+                "n must be less than or equal to the number of options: {}",
+                store.candidates.len()
+            )));
+        }
+        if !is_valid_algorithm_name(&algorithm_name) {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+                algorithm_name
+            )));
+        }
+        check_threshold(&algorithm_name, threshold)?;
+        let tie_break = TieBreak::from_name(tie_break)?;
+        let processed_target = char_vec(target, self.case_sensitive, self.remove_whitespace);
+        let candidates = &store.candidates;
+        let best = match tie_break {
+            TieBreak::Original => {
+                n_best_processed_options(&processed_target, &store.processed, n, &algorithm_name, threshold, layout)?
+            }
+            TieBreak::Shortest => n_best_processed_options_by(
+                &processed_target,
+                &store.processed,
+                n,
+                &algorithm_name,
+                threshold,
+                layout,
+                |i| candidates[i].chars().count(),
+            )?,
+            TieBreak::Lexicographic => n_best_processed_options_by(
+                &processed_target,
+                &store.processed,
+                n,
+                &algorithm_name,
+                threshold,
+                layout,
+                |i| candidates[i].as_str(),
+            )?,
+        };
+        Ok(best
+            .into_iter()
+            .map(|(i, score)| (store.candidates[i].clone(), score))
+            .collect())
+    }
+}
+
+impl Persist for Index {
+    fn write(&self, out: &mut Vec<u8>) {
+        let store = read_lock(&self.store);
+        store.candidates.write(out);
+        store.processed.write(out);
+        self.case_sensitive.write(out);
+        self.remove_whitespace.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = Candidates { candidates: Persist::read(input)?, processed: Persist::read(input)? };
+        if store.processed.len() != store.candidates.len() {
+            return Err(corrupt("candidates and processed candidates differ in length."));
+        }
+        Ok(Index {
+            store: RwLock::new(store),
+            case_sensitive: Persist::read(input)?,
+            remove_whitespace: Persist::read(input)?,
+        })
+    }
+}
+
+impl StoredIndex for Index {
+    const KIND: u32 = 1;
+    const NAME: &'static str = "Index";
+}
+
+#[pymethods]
+impl Index {
+    #[new]
+    #[args(case_sensitive = "false", remove_whitespace = "false")]
+    fn new(candidates: Vec<String>, case_sensitive: bool, remove_whitespace: bool) -> Self {
+        let processed = install(|| {
+            candidates
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|candidate| char_vec(candidate, case_sensitive, remove_whitespace))
+                .collect()
+        });
+        Index { store: RwLock::new(Candidates { candidates, processed }), case_sensitive, remove_whitespace }
+    }
+
+    /// closest(self, target, /, algorithm='levenshtein', threshold=0.0, tie_break='original', layout=None)
+    /// --
+    ///
+    /// Find the closest match to the target string in the indexed candidates.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", tie_break = "\"original\"", layout = "None")]
+    fn closest(
+        &self,
+        py: Python,
+        target: String,
+        algorithm: &str,
+        threshold: f32,
+        tie_break: &str,
+        layout: Option<PyObject>,
+    ) -> PyResult<String> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| {
+            install(|| Ok(self.n_best(&target, 1, algorithm, threshold, tie_break, layout_or_qwerty(&layout))?.remove(0).0))
+        })
+    }
+
+    /// closest_with_score(self, target, /, algorithm='levenshtein', threshold=0.0, tie_break='original', layout=None)
+    /// --
+    ///
+    /// Find the closest match to the target string in the indexed candidates and its score.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", tie_break = "\"original\"", layout = "None")]
+    fn closest_with_score(
+        &self,
+        py: Python,
+        target: String,
+        algorithm: &str,
+        threshold: f32,
+        tie_break: &str,
+        layout: Option<PyObject>,
+    ) -> PyResult<(String, f32)> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| {
+            install(|| Ok(self.n_best(&target, 1, algorithm, threshold, tie_break, layout_or_qwerty(&layout))?.remove(0)))
+        })
+    }
+
+    /// n_closest(self, target, n, /, algorithm='levenshtein', threshold=0.0, tie_break='original', layout=None)
+    /// --
+    ///
+    /// Find the n closest matches to the target string in the indexed candidates.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", tie_break = "\"original\"", layout = "None")]
+    #[allow(clippy::too_many_arguments)]
+    fn n_closest(
+        &self,
+        py: Python,
+        target: String,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        tie_break: &str,
+        layout: Option<PyObject>,
+    ) -> PyResult<Vec<String>> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| {
+            install(|| {
+                Ok(self
+                    .n_best(&target, n, algorithm, threshold, tie_break, layout_or_qwerty(&layout))?
+                    .into_iter()
+                    .map(|(candidate, _)| candidate)
+                    .collect())
+            })
+        })
+    }
+
+    /// n_closest_with_score(self, target, n, /, algorithm='levenshtein', threshold=0.0, tie_break='original', layout=None)
+    /// --
+    ///
+    /// Find the n closest matches to the target string in the indexed candidates and their scores.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", tie_break = "\"original\"", layout = "None")]
+    #[allow(clippy::too_many_arguments)]
+    fn n_closest_with_score(
+        &self,
+        py: Python,
+        target: String,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        tie_break: &str,
+        layout: Option<PyObject>,
+    ) -> PyResult<Vec<(String, f32)>> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| install(|| self.n_best(&target, n, algorithm, threshold, tie_break, layout_or_qwerty(&layout))))
+    }
+
+    /// add(self, candidate, /)
+    /// --
+    ///
+    /// Add a candidate after the existing ones.
+    fn add(&self, candidate: String) {
+        let processed = char_vec(&candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        store.candidates.push(candidate);
+        store.processed.push(processed);
+    }
+
+    /// remove(self, candidate, /)
+    /// --
+    ///
+    /// Remove every copy of the candidate. Raises ValueError when it is not in the index.
+    fn remove(&self, candidate: &str) -> PyResult<()> {
+        let mut store = write_lock(&self.store);
+        let positions = candidate_positions(&store.candidates, candidate)?;
+        for &i in positions.iter().rev() {
+            store.candidates.remove(i);
+            store.processed.remove(i);
+        }
+        Ok(())
+    }
+
+    /// update(self, old, new, /)
+    /// --
+    ///
+    /// Replace every copy of the old candidate with the new one, keeping their positions.
+    /// Raises ValueError when the old candidate is not in the index.
+    fn update(&self, old: &str, new: String) -> PyResult<()> {
+        let processed = char_vec(&new, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, old)? {
+            store.candidates[i] = new.clone();
+            store.processed[i] = processed.clone();
+        }
+        Ok(())
+    }
+
+    #[getter]
+    fn candidates(&self) -> Vec<String> {
+        read_lock(&self.store).candidates.clone()
+    }
+
+    #[getter]
+    fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    #[getter]
+    fn remove_whitespace(&self) -> bool {
+        self.remove_whitespace
+    }
+
+    /// save(self, path, /)
+    /// --
+    ///
+    /// Write the index to a versioned, checksummed binary file that Index.load can read back without rebuilding it.
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_index(self, path)
+    }
+
+    /// load(path, /)
+    /// --
+    ///
+    /// Load an index written by Index.save.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_index(path)
+    }
+
+    fn __len__(&self) -> usize {
+        read_lock(&self.store).candidates.len()
+    }
+}
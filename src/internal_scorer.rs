@@ -1,101 +1,111 @@
+use std::collections::HashMap;
+
 use pyo3::{exceptions::PyValueError, PyResult};
 
-use crate::utils::char_vec;
+use crate::keyboard::Layout;
 
-pub fn levenshtein_distance_target_preprocessed(
-    word1: &str,
-    word2_chars: &Vec<char>,
-    case_sensitive: bool,
-    remove_whitespace: bool,
+pub fn levenshtein_distance_target_matched_preprocessed(
+    word1_chars: &[char],
+    word2_chars: &[char],
     _threshold: f32,
 ) -> PyResult<f32> {
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
-    let n = word1_chars.len();
+    Ok(levenshtein_distance_within(word1_chars, word2_chars, f32::INFINITY).unwrap_or(f32::INFINITY))
+}
+
+/// The Levenshtein distance if it is at most max_distance. No later row of the matrix has a smaller minimum,
+/// so the calculation stops at the first row whose minimum is already over max_distance.
+pub fn levenshtein_distance_within(word1_chars: &[char], word2_chars: &[char], max_distance: f32) -> Option<f32> {
     let m = word2_chars.len();
-    let mut d = vec![0; m + 1];
-    for i in 0..=m {
-        d[i] = i;
-    }
-    for i in 1..=n {
-        let mut prev_diag = d[0];
-        d[0] += 1;
-        let mut prev_col = d[0];
-        let src_chr = word1_chars[i - 1];
-        for j in 1..=m {
-            let mut local_cost = prev_diag;
-            let del_cost = d[j];
-            if src_chr != word2_chars[j - 1] {
-                local_cost = usize::min(local_cost, prev_col);
-                local_cost = usize::min(local_cost, del_cost);
-                local_cost += 1;
-            }
-            prev_col = local_cost;
-            d[j] = local_cost;
-            prev_diag = del_cost;
+    let mut d: Vec<usize> = (0..=m).collect();
+    for &src_chr in word1_chars {
+        let row_min = levenshtein_row(&mut d, src_chr, word2_chars);
+        if row_min as f32 > max_distance {
+            return None;
         }
     }
 
-    return Ok(d[m] as f32);
+    Some(d[m] as f32).filter(|&distance| distance <= max_distance)
+}
+
+/// Advance a row of the Levenshtein matrix in place to the next character of the first word, returning the
+/// new row's minimum. Implementation based on https://turnerj.com/blog/levenshtein-distance-part-3-optimize-everything
+pub fn levenshtein_row(d: &mut [usize], src_chr: char, word2_chars: &[char]) -> usize {
+    let mut prev_diag = d[0];
+    d[0] += 1;
+    let mut prev_col = d[0];
+    let mut row_min = d[0];
+    for j in 1..d.len() {
+        let mut local_cost = prev_diag;
+        let del_cost = d[j];
+        if src_chr != word2_chars[j - 1] {
+            local_cost = usize::min(local_cost, prev_col);
+            local_cost = usize::min(local_cost, del_cost);
+            local_cost += 1;
+        }
+        prev_col = local_cost;
+        d[j] = local_cost;
+        prev_diag = del_cost;
+        row_min = usize::min(row_min, local_cost);
+    }
+    row_min
 }
 
-pub fn jaro_similarity_target_preprocessed(
-    word1: &str,
-    word2_chars: &Vec<char>,
-    case_sensitive: bool,
-    remove_whitespace: bool,
+pub fn damerau_levenshtein_distance_target_matched_preprocessed(
+    word1_chars: &[char],
+    word2_chars: &[char],
     _threshold: f32,
 ) -> PyResult<f32> {
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
-    if word1_chars == *word2_chars {
-        return Ok(1.0);
-    }
+    Ok(damerau_levenshtein_distance_within(word1_chars, word2_chars, f32::INFINITY).unwrap_or(f32::INFINITY))
+}
+
+/// The Damerau-Levenshtein distance if it is at most max_distance, stopping early like levenshtein_distance_within.
+/// Dropping the last character of the first word from an edit script never makes it more expensive, transpositions
+/// included, so row minimums do not decrease here either.
+pub fn damerau_levenshtein_distance_within(word1_chars: &[char], word2_chars: &[char], max_distance: f32) -> Option<f32> {
     let n = word1_chars.len();
     let m = word2_chars.len();
-    let max_dist = (i32::max(m as i32, n as i32) / 2) - 1;
-    let mut matches = 0;
-    let mut hash_word1 = vec![0; n];
-    let mut hash_word2 = vec![0; m];
-    for i in 0..n {
-        let mut j = i32::max(i as i32 - max_dist, 0);
-        while j < i32::min(i as i32 + max_dist + 1, m as i32) {
-            if word1_chars[i] == word2_chars[j as usize] && hash_word2[j as usize] == 0 {
-                hash_word1[i] = 1;
-                hash_word2[j as usize] = 1;
-                matches += 1;
-                break;
-            }
-            j += 1;
-        }
+    let max_dist = n + m;
+    let mut last_row: HashMap<char, usize> = HashMap::new();
+    let mut d = vec![vec![0; m + 2]; n + 2];
+    d[0][0] = max_dist;
+    for i in 0..=n {
+        d[i + 1][0] = max_dist;
+        d[i + 1][1] = i;
     }
-    if matches == 0 {
-        return Ok(0.0);
+    for j in 0..=m {
+        d[0][j + 1] = max_dist;
+        d[1][j + 1] = j;
     }
-    let mut transpositions = 0;
-    let mut point = 0;
-    for i in 0..n {
-        if hash_word1[i] != 0 {
-            while hash_word2[point] == 0 {
-                point += 1;
-            }
-            if word1_chars[i] != word2_chars[point] {
-                point += 1;
-                transpositions += 1;
+    for i in 1..=n {
+        let mut last_match_col = 0;
+        let mut row_min = d[i + 1][1];
+        for j in 1..=m {
+            let k = last_row.get(&word2_chars[j - 1]).copied().unwrap_or(0);
+            let l = last_match_col;
+            let cost = if word1_chars[i - 1] == word2_chars[j - 1] {
+                last_match_col = j;
+                0
             } else {
-                point += 1;
-            }
+                1
+            };
+            d[i + 1][j + 1] = usize::min(
+                usize::min(d[i][j] + cost, d[i + 1][j] + 1),
+                usize::min(d[i][j + 1] + 1, d[k][l] + (i - k - 1) + 1 + (j - l - 1)),
+            );
+            row_min = usize::min(row_min, d[i + 1][j + 1]);
         }
-        transpositions /= 2;
+        if row_min as f32 > max_distance {
+            return None;
+        }
+        last_row.insert(word1_chars[i - 1], i);
     }
-    let jaro_similarity = (matches as f32 / n as f32
-        + matches as f32 / m as f32
-        + (matches - transpositions) as f32 / matches as f32)
-        / 3.0;
-    Ok(jaro_similarity)
+    Some(d[n + 1][m + 1] as f32).filter(|&distance| distance <= max_distance)
 }
 
 pub fn jaro_similarity_target_matched_preprocessed(
-    word1_chars: &Vec<char>,
-    word2_chars: &Vec<char>,
+    word1_chars: &[char],
+    word2_chars: &[char],
+    _threshold: f32,
 ) -> PyResult<f32> {
     if word1_chars == word2_chars {
         return Ok(1.0);
@@ -144,21 +154,18 @@ pub fn jaro_similarity_target_matched_preprocessed(
     Ok(jaro_similarity)
 }
 
-pub fn jaro_winkler_similarity_target_preprocessed(
-    word1: &str,
-    word2_chars: &Vec<char>,
-    case_sensitive: bool,
-    remove_whitespace: bool,
+pub fn jaro_winkler_similarity_target_matched_preprocessed(
+    word1_chars: &[char],
+    word2_chars: &[char],
     threshold: f32,
 ) -> PyResult<f32> {
-    if threshold < 0.0 || threshold > 1.0 {
+    if !(0.0..=1.0).contains(&threshold) {
         return Err(PyValueError::new_err(
             "threshold must be between 0.0 and 1.0",
         ));
     }
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
     let mut jaro_similarity =
-        jaro_similarity_target_matched_preprocessed(&word1_chars, &word2_chars)
+        jaro_similarity_target_matched_preprocessed(word1_chars, word2_chars, threshold)
             .expect("Failed to calculate Jaro similarity.");
     if jaro_similarity > threshold {
         let mut prefix = 0;
@@ -174,19 +181,81 @@ pub fn jaro_winkler_similarity_target_preprocessed(
     Ok(jaro_similarity)
 }
 
-pub fn hamming_distance_target_preprocessed(
-    word1: &str,
-    word2_chars: &Vec<char>,
-    case_sensitive: bool,
-    remove_whitespace: bool,
+pub fn hamming_distance_target_matched_preprocessed(
+    word1_chars: &[char],
+    word2_chars: &[char],
     _threshold: f32,
 ) -> PyResult<f32> {
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
+    Ok(hamming_distance_within(word1_chars, word2_chars, f32::INFINITY).unwrap_or(f32::INFINITY))
+}
+
+/// The Hamming distance if it is at most max_distance, stopping at the first mismatch over it.
+pub fn hamming_distance_within(word1_chars: &[char], word2_chars: &[char], max_distance: f32) -> Option<f32> {
     let mut distance = 0;
     for (i, j) in word1_chars.iter().zip(word2_chars.iter()) {
         if i != j {
             distance += 1;
+            if distance as f32 > max_distance {
+                return None;
+            }
         }
     }
-    Ok(distance as f32)
+    Some(distance as f32)
+}
+
+pub fn weighted_levenshtein<F: Fn(char, char) -> f32>(
+    word1_chars: &[char],
+    word2_chars: &[char],
+    indel_cost: f32,
+    substitution_cost: F,
+) -> f32 {
+    weighted_levenshtein_within(word1_chars, word2_chars, indel_cost, substitution_cost, f32::INFINITY)
+        .unwrap_or(f32::INFINITY)
+}
+
+/// The weighted Levenshtein distance if it is at most max_distance, stopping early like levenshtein_distance_within.
+pub fn weighted_levenshtein_within<F: Fn(char, char) -> f32>(
+    word1_chars: &[char],
+    word2_chars: &[char],
+    indel_cost: f32,
+    substitution_cost: F,
+    max_distance: f32,
+) -> Option<f32> {
+    let m = word2_chars.len();
+    let mut d: Vec<f32> = (0..=m).map(|j| j as f32 * indel_cost).collect();
+    for &src_chr in word1_chars {
+        let mut prev_diag = d[0];
+        d[0] += indel_cost;
+        let mut row_min = d[0];
+        for j in 1..=m {
+            let del_cost = d[j];
+            let local_cost = f32::min(
+                prev_diag + substitution_cost(src_chr, word2_chars[j - 1]),
+                f32::min(d[j - 1], del_cost) + indel_cost,
+            );
+            d[j] = local_cost;
+            prev_diag = del_cost;
+            row_min = f32::min(row_min, local_cost);
+        }
+        if row_min > max_distance {
+            return None;
+        }
+    }
+    Some(d[m]).filter(|&distance| distance <= max_distance)
+}
+
+pub fn typo_distance_target_matched_preprocessed(
+    word1_chars: &[char],
+    word2_chars: &[char],
+    _threshold: f32,
+) -> PyResult<f32> {
+    Ok(typo_distance_with_layout(word1_chars, word2_chars, Layout::qwerty()))
+}
+
+pub fn typo_distance_with_layout(word1_chars: &[char], word2_chars: &[char], layout: &Layout) -> f32 {
+    weighted_levenshtein(word1_chars, word2_chars, 1.0, |a, b| layout.substitution_cost(a, b))
+}
+
+pub fn typo_distance_within(word1_chars: &[char], word2_chars: &[char], layout: &Layout, max_distance: f32) -> Option<f32> {
+    weighted_levenshtein_within(word1_chars, word2_chars, 1.0, |a, b| layout.substitution_cost(a, b), max_distance)
 }
@@ -0,0 +1,255 @@
+use crate::cluster::passes_cutoff;
+use crate::finder::{
+    get_layout_scorer, is_distance_algorithm, is_valid_algorithm_name, score_matrix, score_processed_options,
+    score_processed_options_within,
+};
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py};
+use crate::parallel::{install, min_len};
+use crate::prefilter::has_lower_bound;
+use crate::utils::char_vec;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+pub type JoinRow = (usize, Option<usize>, Option<f32>);
+
+fn compare_scores(a: f32, b: f32, algorithm: &str) -> std::cmp::Ordering {
+    if is_distance_algorithm(algorithm) {
+        a.total_cmp(&b)
+    } else {
+        b.total_cmp(&a)
+    }
+}
+
+/// fuzzy_join(left, right, /, algorithm='levenshtein', cutoff=None, how='inner', one_to_one=False, case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Join each string in left to its best match in right, returning (left_index, right_index, score) rows
+/// ordered by left index. Matches must be within the cutoff when one is given. With how="left", unmatched
+/// left rows are kept as (left_index, None, None). With one_to_one=True each right row is used at most once,
+/// conflicts going to the pair with the best score. With algorithm='typo', layout is the keyboard layout
+/// to measure typos on, as in typo_distance.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    cutoff = "None",
+    how = "\"inner\"",
+    one_to_one = "false",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn fuzzy_join(
+    py: Python,
+    left: Vec<String>,
+    right: Vec<String>,
+    algorithm: &str,
+    cutoff: Option<f32>,
+    how: &str,
+    one_to_one: bool,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<JoinRow>> {
+    let algorithm_name = algorithm.to_uppercase();
+    if !is_valid_algorithm_name(&algorithm_name) {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+            algorithm_name
+        )));
+    }
+    let keep_unmatched = match how.to_lowercase().as_str() {
+        "inner" => false,
+        "left" => true,
+        how_name => {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported join: {}. Supported joins are: inner, left",
+                how_name
+            )))
+        }
+    };
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            let scorer = &get_layout_scorer(&algorithm_name, layout);
+            let processed_left = left
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|string| char_vec(string, case_sensitive, remove_whitespace))
+                .collect::<Vec<_>>();
+            let processed_right = right
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|string| char_vec(string, case_sensitive, remove_whitespace))
+                .collect::<Vec<_>>();
+            let candidates = processed_left
+                .iter()
+                .map(|target| {
+                    let mut scores = match cutoff {
+                        Some(cutoff) if has_lower_bound(&algorithm_name) => {
+                            score_processed_options_within(target, &processed_right, &algorithm_name, cutoff, threshold, layout)?
+                        }
+                        _ => score_processed_options(target, &processed_right, scorer, threshold)?,
+                    };
+                    scores.retain(|&(j, score)| {
+                        (algorithm_name != "HAMMING" || processed_right[j].len() == target.len())
+                            && cutoff.map_or(true, |cutoff| passes_cutoff(score, cutoff, &algorithm_name))
+                    });
+                    Ok(scores)
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+
+            let mut matches: Vec<Option<(usize, f32)>> = vec![None; left.len()];
+            if one_to_one {
+                let mut pairs = candidates
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(i, scores)| scores.into_iter().map(move |(j, score)| (i, j, score)))
+                    .collect::<Vec<_>>();
+                pairs.par_sort_by(|a, b| compare_scores(a.2, b.2, &algorithm_name).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+                let mut right_used = vec![false; right.len()];
+                for (i, j, score) in pairs {
+                    if matches[i].is_none() && !right_used[j] {
+                        matches[i] = Some((j, score));
+                        right_used[j] = true;
+                    }
+                }
+            } else {
+                for (i, scores) in candidates.into_iter().enumerate() {
+                    matches[i] = scores
+                        .into_iter()
+                        .min_by(|a, b| compare_scores(a.1, b.1, &algorithm_name).then(a.0.cmp(&b.0)));
+                }
+            }
+            Ok(matches
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, best)| match best {
+                    Some((j, score)) => Some((i, Some(j), Some(score))),
+                    None if keep_unmatched => Some((i, None, None)),
+                    None => None,
+                })
+                .collect())
+        })
+    })
+}
+
+/// Minimum cost assignment of every row of a rows x cols cost matrix (rows <= cols) to a distinct column,
+/// using the Hungarian algorithm with row and column potentials. Returns the column assigned to each row.
+pub fn hungarian(costs: &[f64], rows: usize, cols: usize) -> Vec<usize> {
+    let mut row_potential = vec![0.0; rows + 1];
+    let mut col_potential = vec![0.0; cols + 1];
+    let mut col_owner = vec![0; cols + 1];
+    let mut way = vec![0; cols + 1];
+    for row in 1..=rows {
+        col_owner[0] = row;
+        let mut col = 0;
+        let mut min_slack = vec![f64::INFINITY; cols + 1];
+        let mut visited = vec![false; cols + 1];
+        loop {
+            visited[col] = true;
+            let current_row = col_owner[col];
+            let mut delta = f64::INFINITY;
+            let mut next_col = 0;
+            for j in 1..=cols {
+                if visited[j] {
+                    continue;
+                }
+                let slack = costs[(current_row - 1) * cols + j - 1] - row_potential[current_row] - col_potential[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = col;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    next_col = j;
+                }
+            }
+            for j in 0..=cols {
+                if visited[j] {
+                    row_potential[col_owner[j]] += delta;
+                    col_potential[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            col = next_col;
+            if col_owner[col] == 0 {
+                break;
+            }
+        }
+        while col != 0 {
+            let prev_col = way[col];
+            col_owner[col] = col_owner[prev_col];
+            col = prev_col;
+        }
+    }
+    let mut assignment = vec![0; rows];
+    for j in 1..=cols {
+        if col_owner[j] != 0 {
+            assignment[col_owner[j] - 1] = j - 1;
+        }
+    }
+    assignment
+}
+
+/// assignment(left, right, /, algorithm='levenshtein', case_sensitive=False, remove_whitespace=False, threshold=0.0, layout=None)
+/// --
+///
+/// Pair strings in left with distinct strings in right so that the total distance is as small as possible
+/// (or the total similarity as large as possible). Returns min(len(left), len(right)) (left_index, right_index, score)
+/// rows ordered by left index. With algorithm='typo', layout is the keyboard layout to measure typos on,
+/// as in typo_distance.
+#[pyfunction(
+    algorithm = "\"levenshtein\"",
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    threshold = "0.0",
+    layout = "None"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn assignment(
+    py: Python,
+    left: Vec<String>,
+    right: Vec<String>,
+    algorithm: &str,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    threshold: f32,
+    layout: Option<PyObject>,
+) -> PyResult<Vec<(usize, usize, f32)>> {
+    let algorithm_name = algorithm.to_uppercase();
+    if !is_valid_algorithm_name(&algorithm_name) {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+            algorithm_name
+        )));
+    }
+    let layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        install(move || {
+            let layout = layout_or_qwerty(&layout);
+            let transposed = left.len() > right.len();
+            let (rows, cols) = if transposed { (&right, &left) } else { (&left, &right) };
+            let scores = score_matrix(rows, cols, &algorithm_name, case_sensitive, remove_whitespace, threshold, layout)?;
+            let sign = if is_distance_algorithm(&algorithm_name) { 1.0 } else { -1.0 };
+            let costs = scores.iter().map(|&score| sign * score as f64).collect::<Vec<_>>();
+            let mut pairs = hungarian(&costs, rows.len(), cols.len())
+                .into_iter()
+                .enumerate()
+                .map(|(row, col)| {
+                    let score = scores[row * cols.len() + col];
+                    if transposed {
+                        (col, row, score)
+                    } else {
+                        (row, col, score)
+                    }
+                })
+                .collect::<Vec<_>>();
+            pairs.sort_unstable_by_key(|pair| pair.0);
+            Ok(pairs)
+        })
+    })
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+const ROW_OFFSETS: [f32; 4] = [0.0, 0.5, 0.75, 1.25];
+
+const QWERTY_ROWS: [&str; 4] = ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"];
+const AZERTY_ROWS: [&str; 4] = ["1234567890)=", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"];
+const DVORAK_ROWS: [&str; 4] = ["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"];
+
+pub struct Layout {
+    positions: HashMap<char, (f32, f32)>,
+}
+
+impl Layout {
+    pub fn from_rows<S: AsRef<str>>(rows: &[S]) -> Layout {
+        let mut positions = HashMap::new();
+        for (row, keys) in rows.iter().enumerate() {
+            let offset = ROW_OFFSETS.get(row).copied().unwrap_or(0.0);
+            for (col, key) in keys.as_ref().chars().enumerate() {
+                for lower in key.to_lowercase() {
+                    positions.insert(lower, (row as f32, col as f32 + offset));
+                }
+            }
+        }
+        Layout { positions }
+    }
+
+    pub fn named(name: &str) -> PyResult<&'static Layout> {
+        static QWERTY: OnceLock<Layout> = OnceLock::new();
+        static AZERTY: OnceLock<Layout> = OnceLock::new();
+        static DVORAK: OnceLock<Layout> = OnceLock::new();
+        match name.to_uppercase().as_str() {
+            "QWERTY" => Ok(QWERTY.get_or_init(|| Layout::from_rows(&QWERTY_ROWS))),
+            "AZERTY" => Ok(AZERTY.get_or_init(|| Layout::from_rows(&AZERTY_ROWS))),
+            "DVORAK" => Ok(DVORAK.get_or_init(|| Layout::from_rows(&DVORAK_ROWS))),
+            layout_name => Err(PyValueError::new_err(format!(
+                "Unsupported keyboard layout: {}. Supported layouts are: QWERTY, AZERTY, DVORAK",
+                layout_name
+            ))),
+        }
+    }
+
+    pub fn qwerty() -> &'static Layout {
+        Layout::named("QWERTY").expect("QWERTY layout is always available.")
+    }
+
+    fn position(&self, key: char) -> Option<(f32, f32)> {
+        if let Some(position) = self.positions.get(&key) {
+            return Some(*position);
+        }
+        key.to_lowercase()
+            .next()
+            .and_then(|lower| self.positions.get(&lower).copied())
+    }
+
+    /// Substitution cost between two keys: half the distance between them on the keyboard,
+    /// so a neighboring key costs about 0.5 and keys two or more apart cost a full edit.
+    pub fn substitution_cost(&self, a: char, b: char) -> f32 {
+        if a == b {
+            return 0.0;
+        }
+        match (self.position(a), self.position(b)) {
+            (Some((row_a, col_a)), Some((row_b, col_b))) => {
+                let distance = ((row_a - row_b).powi(2) + (col_a - col_b).powi(2)).sqrt();
+                f32::min(1.0, distance / 2.0)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// Parse an optional layout argument, where None stands for QWERTY.
+pub fn optional_layout_from_py(py: Python, layout: Option<PyObject>) -> PyResult<Option<Layout>> {
+    layout.map(|layout| layout_from_py(layout.as_ref(py))).transpose()
+}
+
+pub fn layout_or_qwerty(layout: &Option<Layout>) -> &Layout {
+    match layout {
+        Some(layout) => layout,
+        None => Layout::qwerty(),
+    }
+}
+
+pub fn layout_from_py(layout: &PyAny) -> PyResult<Layout> {
+    if let Ok(name) = layout.extract::<&str>() {
+        let named = Layout::named(name)?;
+        return Ok(Layout { positions: named.positions.clone() });
+    }
+    match layout.extract::<Vec<&str>>() {
+        Ok(rows) if !rows.is_empty() => Ok(Layout::from_rows(&rows)),
+        _ => Err(PyValueError::new_err(
+            "layout must be a layout name or a non-empty list of keyboard rows.",
+        )),
+    }
+}
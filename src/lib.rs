@@ -1,28 +1,83 @@
 mod scorer;
 mod internal_scorer;
 mod finder;
+mod editops;
+mod keyboard;
+mod cluster;
+mod join;
+mod awaitable;
+mod index;
+mod bktree;
+mod symspell;
+mod trie;
+mod qgram;
+mod prefilter;
+mod topk;
+mod parallel;
+mod persist;
 mod utils;
 
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
 use scorer::*;
 use finder::*;
+use editops::*;
+use cluster::*;
+use join::*;
+use awaitable::*;
+use parallel::*;
+use index::Index;
+use bktree::BKTree;
+use symspell::SymSpell;
+use trie::Trie;
+use qgram::QGramIndex;
 
 #[pymodule]
 fn ffzf(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_wrapped(wrap_pyfunction!(levenshtein_distance))?;
+    m.add_wrapped(wrap_pyfunction!(damerau_levenshtein_distance))?;
     m.add_wrapped(wrap_pyfunction!(hamming_distance))?;
     m.add_wrapped(wrap_pyfunction!(jaro_similarity))?;
     m.add_wrapped(wrap_pyfunction!(jaro_winkler_similarity))?;
+    m.add_wrapped(wrap_pyfunction!(typo_distance))?;
     m.add_wrapped(wrap_pyfunction!(closest))?;
     m.add_wrapped(wrap_pyfunction!(n_closest))?;
     m.add_wrapped(wrap_pyfunction!(closest_index_pair))?;
     m.add_wrapped(wrap_pyfunction!(closest_with_score))?;
+    m.add_wrapped(wrap_pyfunction!(closest_all))?;
     m.add_wrapped(wrap_pyfunction!(n_closest_with_score))?;
+    m.add_wrapped(wrap_pyfunction!(closest_many))?;
+    m.add_wrapped(wrap_pyfunction!(n_closest_many))?;
+    m.add_wrapped(wrap_pyfunction!(cdist))?;
+    m.add_wrapped(wrap_pyfunction!(closest_async))?;
+    m.add_wrapped(wrap_pyfunction!(n_closest_async))?;
+    m.add_wrapped(wrap_pyfunction!(cdist_async))?;
+    m.add_wrapped(wrap_pyfunction!(dedupe))?;
+    m.add_wrapped(wrap_pyfunction!(cluster))?;
+    m.add_wrapped(wrap_pyfunction!(fuzzy_join))?;
+    m.add_wrapped(wrap_pyfunction!(assignment))?;
+    m.add_wrapped(wrap_pyfunction!(editops))?;
+    m.add_wrapped(wrap_pyfunction!(opcodes))?;
+    m.add_wrapped(wrap_pyfunction!(matching_blocks))?;
+    m.add_wrapped(wrap_pyfunction!(alignment))?;
+    m.add_wrapped(wrap_pyfunction!(format_alignment))?;
+    m.add_wrapped(wrap_pyfunction!(apply_editops))?;
+    m.add_wrapped(wrap_pyfunction!(inverse_editops))?;
+    m.add_wrapped(wrap_pyfunction!(set_num_threads))?;
+    m.add_wrapped(wrap_pyfunction!(get_num_threads))?;
+    m.add_wrapped(wrap_pyfunction!(set_parallel_threshold))?;
+    m.add_wrapped(wrap_pyfunction!(get_parallel_threshold))?;
+    m.add_class::<Index>()?;
+    m.add_class::<BKTree>()?;
+    m.add_class::<SymSpell>()?;
+    m.add_class::<Trie>()?;
+    m.add_class::<QGramIndex>()?;
     m.add("LEVENSHTEIN", "LEVENSHTEIN")?;
+    m.add("DAMERAU", "DAMERAU")?;
     m.add("JARO", "JARO")?;
     m.add("JAROWINKLER", "JAROWINKLER")?;
     m.add("HAMMING", "HAMMING")?;
+    m.add("TYPO", "TYPO")?;
     Ok(())
 }
 
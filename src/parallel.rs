@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::utils::{read_lock, write_lock};
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+// None until set_num_threads is called, meaning rayon's global pool is used.
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+static PARALLEL_THRESHOLD: AtomicUsize = AtomicUsize::new(1000);
+
+fn pool() -> Option<Arc<ThreadPool>> {
+    read_lock(&POOL).clone()
+}
+
+/// Run a job on the configured thread pool, so that every parallel iterator inside it uses that pool.
+pub fn install<R: Send>(job: impl FnOnce() -> R + Send) -> R {
+    match pool() {
+        Some(pool) => pool.install(job),
+        None => job(),
+    }
+}
+
+/// Run a job in the background on the configured thread pool.
+pub fn spawn(job: impl FnOnce() + Send + 'static) {
+    match pool() {
+        Some(pool) => pool.spawn(job),
+        None => rayon::spawn(job),
+    }
+}
+
+pub fn parallel_threshold() -> usize {
+    PARALLEL_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// The smallest piece a parallel iterator over candidates may be split into. Rayon only splits a list
+/// into pieces of at least this length, so a list shorter than the parallel threshold is never split between threads.
+pub fn min_len() -> usize {
+    min_len_weighted(1)
+}
+
+/// min_len for an iterator whose items each stand for `weight` comparisons, such as one query scored against every choice.
+pub fn min_len_weighted(weight: usize) -> usize {
+    usize::max(1, parallel_threshold().div_ceil(2) / usize::max(1, weight))
+}
+
+/// set_num_threads(num_threads, /)
+/// --
+///
+/// Set the number of worker threads used by the finders, cdist, dedupe, cluster, fuzzy_join, assignment and the
+/// Index, BKTree, SymSpell, Trie and QGramIndex classes. 1 runs everything on a single worker thread, for use inside
+/// processes that are already parallel, and 0 goes back to rayon's default pool, sized by RAYON_NUM_THREADS or the
+/// number of CPUs.
+#[pyfunction]
+pub fn set_num_threads(num_threads: usize) -> PyResult<()> {
+    let pool = if num_threads == 0 {
+        None
+    } else {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("ffzf-{}", i))
+            .build()
+            .map_err(|error| PyRuntimeError::new_err(format!("Could not start thread pool: {}", error)))?;
+        Some(Arc::new(pool))
+    };
+    *write_lock(&POOL) = pool;
+    Ok(())
+}
+
+/// get_num_threads()
+/// --
+///
+/// The number of worker threads used by the finders, cdist, dedupe, cluster, fuzzy_join, assignment and the indexes.
+#[pyfunction]
+pub fn get_num_threads() -> usize {
+    match pool() {
+        Some(pool) => pool.current_num_threads(),
+        None => rayon::current_num_threads(),
+    }
+}
+
+/// set_parallel_threshold(threshold, /)
+/// --
+///
+/// Set the number of candidates below which matching runs sequentially on the calling thread,
+/// where splitting the work between threads would cost more than it saves. Defaults to 1000.
+#[pyfunction]
+pub fn set_parallel_threshold(threshold: usize) {
+    PARALLEL_THRESHOLD.store(threshold, Ordering::Relaxed);
+}
+
+/// get_parallel_threshold()
+/// --
+///
+/// The number of candidates below which matching runs sequentially.
+#[pyfunction]
+pub fn get_parallel_threshold() -> usize {
+    parallel_threshold()
+}
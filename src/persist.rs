@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{Read, Write};
+
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    prelude::*,
+};
+
+const MAGIC: &[u8; 4] = b"FFZF";
+pub const FORMAT_VERSION: u32 = 2;
+// Magic, format version, index kind, payload length and payload checksum.
+const HEADER_LENGTH: usize = 4 + 4 + 4 + 8 + 4;
+
+/// Values written to index files as little-endian fields with length-prefixed sequences,
+/// so that a file reads the same on every platform. Implementations for types holding positions into
+/// other fields check them after reading, so a corrupt file is rejected instead of panicking on a later search.
+pub trait Persist: Sized {
+    fn write(&self, out: &mut Vec<u8>);
+    fn read(input: &mut Reader) -> PyResult<Self>;
+}
+
+/// An index type that can be saved on its own. KIND identifies it in the file header.
+pub trait StoredIndex: Persist {
+    const KIND: u32;
+    const NAME: &'static str;
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> PyResult<&'a [u8]> {
+        if self.data.len() - self.position < length {
+            return Err(PyValueError::new_err("Corrupt index file: unexpected end of data."));
+        }
+        let bytes = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> PyResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+impl Persist for bool {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        match input.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(PyValueError::new_err(format!("Corrupt index file: invalid boolean {}.", byte))),
+        }
+    }
+}
+
+impl Persist for u32 {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(u32::from_le_bytes(input.take_array()?))
+    }
+}
+
+impl Persist for u64 {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(u64::from_le_bytes(input.take_array()?))
+    }
+}
+
+impl Persist for usize {
+    fn write(&self, out: &mut Vec<u8>) {
+        (*self as u64).write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        usize::try_from(u64::read(input)?)
+            .map_err(|_| PyValueError::new_err("Corrupt index file: length does not fit in memory."))
+    }
+}
+
+impl Persist for char {
+    fn write(&self, out: &mut Vec<u8>) {
+        (*self as u32).write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let code = u32::read(input)?;
+        char::from_u32(code)
+            .ok_or_else(|| PyValueError::new_err(format!("Corrupt index file: invalid character {:#x}.", code)))
+    }
+}
+
+impl Persist for String {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.len().write(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let length = usize::read(input)?;
+        String::from_utf8(input.take(length)?.to_vec())
+            .map_err(|_| PyValueError::new_err("Corrupt index file: invalid UTF-8 string."))
+    }
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.len().write(out);
+        for item in self {
+            item.write(out);
+        }
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let length = usize::read(input)?;
+        // Every item takes at least one byte, which keeps a corrupt length from reserving huge amounts of memory.
+        let mut items = Vec::with_capacity(usize::min(length, input.data.len() - input.position));
+        for _ in 0..length {
+            items.push(T::read(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Persist> Persist for Option<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.is_some().write(out);
+        if let Some(value) = self {
+            value.write(out);
+        }
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        if bool::read(input)? {
+            Ok(Some(T::read(input)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+        self.1.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok((A::read(input)?, B::read(input)?))
+    }
+}
+
+impl<K: Persist + Eq + Hash, V: Persist> Persist for HashMap<K, V> {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.len().write(out);
+        for (key, value) in self {
+            key.write(out);
+            value.write(out);
+        }
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(Vec::<(K, V)>::read(input)?.into_iter().collect())
+    }
+}
+
+pub fn corrupt(message: &str) -> PyErr {
+    PyValueError::new_err(format!("Corrupt index file: {}", message))
+}
+
+/// Check that a position read from a file points into a sequence of the given length.
+pub fn check_position(position: usize, length: usize, what: &str) -> PyResult<()> {
+    if position >= length {
+        return Err(corrupt(&format!("{} {} is out of range.", what, position)));
+    }
+    Ok(())
+}
+
+/// Check that a position read from a file points at a candidate that has not been removed.
+pub fn check_candidate(position: usize, candidates: &[Option<String>]) -> PyResult<()> {
+    check_position(position, candidates.len(), "candidate")?;
+    if candidates[position].is_none() {
+        return Err(corrupt(&format!("removed candidate {} is still indexed.", position)));
+    }
+    Ok(())
+}
+
+/// Check that a stored count matches the candidates that have not been removed.
+pub fn check_count(count: usize, candidates: &[Option<String>]) -> PyResult<()> {
+    if candidates.iter().filter(|candidate| candidate.is_some()).count() != count {
+        return Err(corrupt("candidate count does not match the candidates."));
+    }
+    Ok(())
+}
+
+pub fn save_index<T: StoredIndex>(index: &T, path: &str) -> PyResult<()> {
+    let mut payload = Vec::new();
+    index.write(&mut payload);
+    let mut contents = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    contents.extend_from_slice(MAGIC);
+    FORMAT_VERSION.write(&mut contents);
+    T::KIND.write(&mut contents);
+    payload.len().write(&mut contents);
+    crc32fast::hash(&payload).write(&mut contents);
+    contents.extend_from_slice(&payload);
+    File::create(path)
+        .and_then(|mut file| file.write_all(&contents))
+        .map_err(|error| PyIOError::new_err(format!("Could not write index file {}: {}", path, error)))
+}
+
+fn decode_index<T: StoredIndex>(contents: &[u8], path: &str) -> PyResult<T> {
+    if contents.len() < HEADER_LENGTH || &contents[..4] != MAGIC {
+        return Err(PyValueError::new_err(format!("Not an ffzf index file: {}", path)));
+    }
+    let mut input = Reader { data: contents, position: 4 };
+    let version = u32::read(&mut input)?;
+    if version != FORMAT_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported index file version: {}. This version of ffzf reads version {} files; rebuild and save the index again.",
+            version, FORMAT_VERSION
+        )));
+    }
+    let kind = u32::read(&mut input)?;
+    if kind != T::KIND {
+        return Err(PyValueError::new_err(format!(
+            "{} does not hold a {} index.",
+            path,
+            T::NAME
+        )));
+    }
+    let length = usize::read(&mut input)?;
+    let checksum = u32::read(&mut input)?;
+    let payload = &contents[HEADER_LENGTH..];
+    if payload.len() != length || crc32fast::hash(payload) != checksum {
+        return Err(PyValueError::new_err(format!(
+            "Index file checksum mismatch: {} is truncated or corrupt.",
+            path
+        )));
+    }
+    let mut input = Reader { data: payload, position: 0 };
+    let index = T::read(&mut input)?;
+    if input.position != payload.len() {
+        return Err(PyValueError::new_err("Corrupt index file: trailing data."));
+    }
+    Ok(index)
+}
+
+/// Load an index saved with save_index. The whole file is read and decoded into owned structures. Files are not
+/// memory-mapped: the indexes are built from hash maps and nested vectors, which have no layout usable in place.
+pub fn load_index<T: StoredIndex>(path: &str) -> PyResult<T> {
+    let mut contents = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut contents))
+        .map_err(|error| PyIOError::new_err(format!("Could not read index file {}: {}", path, error)))?;
+    decode_index(&contents, path)
+}
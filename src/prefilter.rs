@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+/// The characters of a word with their counts, for the bag distance between two words.
+pub struct CharBag {
+    counts: HashMap<char, usize>,
+    length: usize,
+}
+
+impl CharBag {
+    pub fn new(chars: &[char]) -> CharBag {
+        let mut counts = HashMap::new();
+        for &chr in chars {
+            *counts.entry(chr).or_insert(0) += 1;
+        }
+        CharBag { counts, length: chars.len() }
+    }
+
+    /// The larger of the number of characters in either word that the other lacks. Every insertion, deletion
+    /// or substitution changes it by at most one and a transposition leaves it unchanged, so it is a lower bound
+    /// on the Levenshtein, Damerau-Levenshtein and Hamming distances.
+    pub fn distance(&self, other: &CharBag) -> usize {
+        let common = self
+            .counts
+            .iter()
+            .map(|(chr, &count)| usize::min(count, other.counts.get(chr).copied().unwrap_or(0)))
+            .sum::<usize>();
+        usize::max(self.length - common, other.length - common)
+    }
+
+    /// Whether the two words are certainly further apart than max_distance, checking the difference in length
+    /// before the bag distance. Only the length applies to typo distance, where substituting a neighboring key
+    /// costs less than a full edit.
+    pub fn exceeds(&self, other: &CharBag, algorithm: &str, max_distance: f32) -> bool {
+        self.length.abs_diff(other.length) as f32 > max_distance
+            || (algorithm != "TYPO" && self.distance(other) as f32 > max_distance)
+    }
+}
+
+pub fn has_lower_bound(algorithm: &str) -> bool {
+    ["LEVENSHTEIN", "DAMERAU", "HAMMING", "TYPO"].contains(&algorithm)
+}
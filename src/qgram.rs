@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::finder::{get_layout_scorer, is_valid_algorithm_name, sort_scores};
+use crate::internal_scorer::levenshtein_distance_within;
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py, Layout};
+use crate::parallel::{install, min_len};
+use crate::persist::{check_candidate, check_count, corrupt, load_index, save_index, Persist, Reader, StoredIndex};
+use crate::utils::{candidate_positions, char_vec, read_lock, write_lock};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+const PAD_START: char = '\u{2}';
+const PAD_END: char = '\u{3}';
+
+/// Count the q-grams of a word padded with q - 1 sentinels on each side,
+/// so a word of length l always has l + q - 1 of them.
+pub fn qgram_counts(word: &[char], q: usize) -> HashMap<Vec<char>, u32> {
+    let mut padded = vec![PAD_START; q - 1];
+    padded.extend_from_slice(word);
+    padded.extend(std::iter::repeat(PAD_END).take(q - 1));
+    let mut counts = HashMap::new();
+    for gram in padded.windows(q) {
+        *counts.entry(gram.to_vec()).or_insert(0) += 1;
+    }
+    counts
+}
+
+struct QGramStore {
+    // Removed candidates leave a None behind so that the positions of the others do not change.
+    candidates: Vec<Option<String>>,
+    processed: Vec<Vec<char>>,
+    postings: HashMap<Vec<char>, Vec<(usize, u32)>>,
+    by_length: Vec<Vec<usize>>,
+    q: usize,
+    count: usize,
+}
+
+impl QGramStore {
+    fn insert(&mut self, index: usize, chars: Vec<char>) {
+        for (gram, count) in qgram_counts(&chars, self.q) {
+            self.postings.entry(gram).or_default().push((index, count));
+        }
+        if self.by_length.len() <= chars.len() {
+            self.by_length.resize(chars.len() + 1, Vec::new());
+        }
+        self.by_length[chars.len()].push(index);
+        self.processed[index] = chars;
+    }
+
+    fn detach(&mut self, index: usize) {
+        for gram in qgram_counts(&self.processed[index], self.q).into_keys() {
+            if let Some(posting) = self.postings.get_mut(&gram) {
+                posting.retain(|&(i, _)| i != index);
+                if posting.is_empty() {
+                    self.postings.remove(&gram);
+                }
+            }
+        }
+        self.by_length[self.processed[index].len()].retain(|&i| i != index);
+        self.processed[index] = Vec::new();
+    }
+
+    fn candidate(&self, index: usize) -> String {
+        self.candidates[index]
+            .clone()
+            .expect("Postings only hold candidates that have not been removed.")
+    }
+
+    // Searches read the length bucket of every candidate they consider, and of every length up to the longest.
+    fn validate(&self) -> PyResult<()> {
+        if self.q < 1 {
+            return Err(corrupt("q must be greater than 0."));
+        }
+        if self.processed.len() != self.candidates.len() {
+            return Err(corrupt("candidates and processed candidates differ in length."));
+        }
+        for &(i, _) in self.postings.values().flatten() {
+            check_candidate(i, &self.candidates)?;
+        }
+        if self.by_length.is_empty() {
+            return Err(corrupt("the length buckets are missing."));
+        }
+        for (length, bucket) in self.by_length.iter().enumerate() {
+            for &i in bucket {
+                check_candidate(i, &self.candidates)?;
+                if self.processed[i].len() != length {
+                    return Err(corrupt(&format!("candidate {} is in the wrong length bucket.", i)));
+                }
+            }
+        }
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            if candidate.is_some() && self.processed[i].len() >= self.by_length.len() {
+                return Err(corrupt(&format!("candidate {} has no length bucket.", i)));
+            }
+        }
+        check_count(self.count, &self.candidates)
+    }
+
+    fn shared_counts(&self, query: &[char]) -> HashMap<usize, u32> {
+        let mut shared = HashMap::new();
+        for (gram, query_count) in qgram_counts(query, self.q) {
+            for &(i, count) in self.postings.get(&gram).into_iter().flatten() {
+                *shared.entry(i).or_insert(0) += u32::min(query_count, count);
+            }
+        }
+        shared
+    }
+
+    // Each edit destroys at most q of the q-grams, so two words within max_distance edits share at least
+    // max(l1, l2) + q - 1 - max_distance * q of them. Candidates for which that bound is not positive
+    // cannot be filtered by their q-grams and are taken from the length buckets instead.
+    fn within(&self, query: &[char], max_distance: usize) -> Vec<(usize, usize)> {
+        let shared = self.shared_counts(query);
+        let required = |length: usize| {
+            (usize::max(query.len(), length) + self.q - 1).saturating_sub(max_distance.saturating_mul(self.q))
+        };
+        let min_length = query.len().saturating_sub(max_distance);
+        let max_length = usize::min(query.len().saturating_add(max_distance), self.by_length.len().saturating_sub(1));
+        let mut shortlist = shared
+            .into_iter()
+            .filter(|&(i, count)| {
+                let length = self.processed[i].len();
+                length >= min_length && length <= max_length && required(length) > 0 && count as usize >= required(length)
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        for length in min_length..=max_length {
+            if required(length) == 0 {
+                shortlist.extend_from_slice(&self.by_length[length]);
+            }
+        }
+        let mut found = install(|| {
+            shortlist
+                .into_par_iter()
+                .with_min_len(min_len())
+                .filter_map(|i| {
+                    levenshtein_distance_within(&self.processed[i], query, max_distance as f32)
+                        .map(|distance| (i, distance as usize))
+                })
+                .collect::<Vec<_>>()
+        });
+        found.sort_unstable_by_key(|&(i, distance)| (distance, i));
+        found
+    }
+}
+
+impl Persist for QGramStore {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.candidates.write(out);
+        self.processed.write(out);
+        self.postings.write(out);
+        self.by_length.write(out);
+        self.q.write(out);
+        self.count.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = QGramStore {
+            candidates: Persist::read(input)?,
+            processed: Persist::read(input)?,
+            postings: Persist::read(input)?,
+            by_length: Persist::read(input)?,
+            q: Persist::read(input)?,
+            count: Persist::read(input)?,
+        };
+        store.validate()?;
+        Ok(store)
+    }
+}
+
+/// QGramIndex(candidates, /, q=3, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// An inverted index from q-grams to the candidates containing them. Lookups only consider the candidates
+/// sharing enough q-grams with the query, which suits large corpora of long strings such as addresses or titles.
+/// Candidates can be added, removed and updated without rebuilding the index.
+#[pyclass]
+pub struct QGramIndex {
+    store: RwLock<QGramStore>,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+}
+
+impl QGramIndex {
+    fn n_best(
+        &self,
+        target: &str,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        min_shared: u32,
+        layout: &Layout,
+    ) -> PyResult<Vec<(String, f32)>> {
+        let algorithm_name = algorithm.to_uppercase();
+        let store = read_lock(&self.store);
+        if store.count == 0 {
+            return Err(PyValueError::new_err("No options provided."));
+        }
+        if n < 1 {
+            return Err(PyValueError::new_err("n must be greater than 0."));
+        }
+        else if n > store.count {
+            return Err(PyValueError::new_err(format!(
+                "n must be less than or equal to the number of options: {}",
+                store.count
+            )));
+        }
+        if !is_valid_algorithm_name(&algorithm_name) {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported algorithm: {}. Supported algorithms are: LEVENSHTEIN, DAMERAU, JARO, JAROWINKLER, HAMMING, TYPO",
+                algorithm_name
+            )));
+        }
+        let processed_target = char_vec(target, self.case_sensitive, self.remove_whitespace);
+        let scorer = get_layout_scorer(&algorithm_name, layout);
+        let shortlist = store
+            .shared_counts(&processed_target)
+            .into_iter()
+            .filter(|&(i, count)| {
+                count >= min_shared
+                    && (algorithm_name != "HAMMING" || store.processed[i].len() == processed_target.len())
+            })
+            .collect::<Vec<_>>();
+        let mut scores = install(|| {
+            shortlist
+                .into_par_iter()
+                .with_min_len(min_len())
+                .map(|(i, _)| Ok((i, scorer(&store.processed[i], &processed_target, threshold)?)))
+                .collect::<PyResult<Vec<_>>>()
+        })?;
+        sort_scores(&mut scores, &algorithm_name);
+        scores.truncate(n);
+        Ok(scores
+            .into_iter()
+            .map(|(i, score)| (store.candidate(i), score))
+            .collect())
+    }
+}
+
+impl Persist for QGramIndex {
+    fn write(&self, out: &mut Vec<u8>) {
+        read_lock(&self.store).write(out);
+        self.case_sensitive.write(out);
+        self.remove_whitespace.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(QGramIndex {
+            store: RwLock::new(QGramStore::read(input)?),
+            case_sensitive: Persist::read(input)?,
+            remove_whitespace: Persist::read(input)?,
+        })
+    }
+}
+
+impl StoredIndex for QGramIndex {
+    const KIND: u32 = 5;
+    const NAME: &'static str = "QGramIndex";
+}
+
+#[pymethods]
+impl QGramIndex {
+    #[new]
+    #[args(q = "3", case_sensitive = "false", remove_whitespace = "false")]
+    fn new(candidates: Vec<String>, q: usize, case_sensitive: bool, remove_whitespace: bool) -> PyResult<Self> {
+        if q < 1 {
+            return Err(PyValueError::new_err("q must be greater than 0."));
+        }
+        let (processed, grams) = install(|| {
+            let processed = candidates
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|candidate| char_vec(candidate, case_sensitive, remove_whitespace))
+                .collect::<Vec<_>>();
+            let grams = processed
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|chars| qgram_counts(chars, q))
+                .collect::<Vec<_>>();
+            (processed, grams)
+        });
+        let mut postings: HashMap<Vec<char>, Vec<(usize, u32)>> = HashMap::new();
+        for (i, counts) in grams.into_iter().enumerate() {
+            for (gram, count) in counts {
+                postings.entry(gram).or_default().push((i, count));
+            }
+        }
+        let mut by_length: Vec<Vec<usize>> = vec![Vec::new()];
+        for (i, chars) in processed.iter().enumerate() {
+            if by_length.len() <= chars.len() {
+                by_length.resize(chars.len() + 1, Vec::new());
+            }
+            by_length[chars.len()].push(i);
+        }
+        let store = QGramStore {
+            count: candidates.len(),
+            candidates: candidates.into_iter().map(Some).collect(),
+            processed,
+            postings,
+            by_length,
+            q,
+        };
+        Ok(QGramIndex { store: RwLock::new(store), case_sensitive, remove_whitespace })
+    }
+
+    /// search(self, query, max_distance, /)
+    /// --
+    ///
+    /// Find every candidate within max_distance Levenshtein edits of the query, as (candidate, distance)
+    /// pairs ordered by distance, then by position in the candidates. Only candidates passing the q-gram
+    /// count filter are scored, and the filter never drops a candidate within the distance.
+    fn search(&self, py: Python, query: String, max_distance: usize) -> Vec<(String, f32)> {
+        py.allow_threads(|| {
+            install(|| {
+                let processed_query = char_vec(&query, self.case_sensitive, self.remove_whitespace);
+                let store = read_lock(&self.store);
+                store
+                    .within(&processed_query, max_distance)
+                    .into_iter()
+                    .map(|(i, distance)| (store.candidate(i), distance as f32))
+                    .collect()
+            })
+        })
+    }
+
+    /// n_closest(self, target, n, /, algorithm='levenshtein', threshold=0.0, min_shared=1, layout=None)
+    /// --
+    ///
+    /// Find up to n closest matches to the target string among the candidates sharing at least
+    /// min_shared q-grams with it. With algorithm='typo', layout is the keyboard layout to measure typos on.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", min_shared = "1", layout = "None")]
+    #[allow(clippy::too_many_arguments)]
+    fn n_closest(
+        &self,
+        py: Python,
+        target: String,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        min_shared: u32,
+        layout: Option<PyObject>,
+    ) -> PyResult<Vec<String>> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| {
+            install(|| {
+                Ok(self
+                    .n_best(&target, n, algorithm, threshold, min_shared, layout_or_qwerty(&layout))?
+                    .into_iter()
+                    .map(|(candidate, _)| candidate)
+                    .collect())
+            })
+        })
+    }
+
+    /// n_closest_with_score(self, target, n, /, algorithm='levenshtein', threshold=0.0, min_shared=1, layout=None)
+    /// --
+    ///
+    /// Find up to n closest matches to the target string among the candidates sharing at least
+    /// min_shared q-grams with it, and their scores. With algorithm='typo', layout is the keyboard layout
+    /// to measure typos on.
+    #[args(algorithm = "\"levenshtein\"", threshold = "0.0", min_shared = "1", layout = "None")]
+    #[allow(clippy::too_many_arguments)]
+    fn n_closest_with_score(
+        &self,
+        py: Python,
+        target: String,
+        n: usize,
+        algorithm: &str,
+        threshold: f32,
+        min_shared: u32,
+        layout: Option<PyObject>,
+    ) -> PyResult<Vec<(String, f32)>> {
+        let layout = optional_layout_from_py(py, layout)?;
+        py.allow_threads(|| install(|| self.n_best(&target, n, algorithm, threshold, min_shared, layout_or_qwerty(&layout))))
+    }
+
+    /// add(self, candidate, /)
+    /// --
+    ///
+    /// Add a candidate after the existing ones.
+    fn add(&self, candidate: String) {
+        let processed = char_vec(&candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        let index = store.candidates.len();
+        store.candidates.push(Some(candidate));
+        store.processed.push(Vec::new());
+        store.insert(index, processed);
+        store.count += 1;
+    }
+
+    /// remove(self, candidate, /)
+    /// --
+    ///
+    /// Remove every copy of the candidate. Raises ValueError when it is not in the index.
+    fn remove(&self, candidate: &str) -> PyResult<()> {
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, candidate)? {
+            store.detach(i);
+            store.candidates[i] = None;
+            store.count -= 1;
+        }
+        Ok(())
+    }
+
+    /// update(self, old, new, /)
+    /// --
+    ///
+    /// Replace every copy of the old candidate with the new one, keeping their positions.
+    /// Raises ValueError when the old candidate is not in the index.
+    fn update(&self, old: &str, new: String) -> PyResult<()> {
+        let processed = char_vec(&new, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, old)? {
+            store.detach(i);
+            store.insert(i, processed.clone());
+            store.candidates[i] = Some(new.clone());
+        }
+        Ok(())
+    }
+
+    #[getter]
+    fn q(&self) -> usize {
+        read_lock(&self.store).q
+    }
+
+    /// save(self, path, /)
+    /// --
+    ///
+    /// Write the index to a versioned, checksummed binary file that QGramIndex.load can read back without rebuilding it.
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_index(self, path)
+    }
+
+    /// load(path, /)
+    /// --
+    ///
+    /// Load an index written by QGramIndex.save.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_index(path)
+    }
+
+    fn __len__(&self) -> usize {
+        read_lock(&self.store).count
+    }
+}
@@ -1,3 +1,7 @@
+use crate::internal_scorer::{
+    damerau_levenshtein_distance_target_matched_preprocessed, levenshtein_row, typo_distance_with_layout,
+};
+use crate::keyboard::{layout_or_qwerty, optional_layout_from_py};
 use crate::utils::char_vec;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
@@ -12,39 +16,45 @@ use pyo3::prelude::*;
     remove_whitespace = "false"
 )]
 pub fn levenshtein_distance(
-    word1: &str,
-    word2: &str,
+    py: Python,
+    word1: String,
+    word2: String,
     case_sensitive: bool,
     remove_whitespace: bool
 ) -> PyResult<i32> {
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
-    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
-    let n = word1_chars.len();
-    let m = word2_chars.len();
-    let mut d = vec![0; m + 1];
-    for i in 0..=m {
-        d[i] = i;
-    }
-    for i in 1..=n {
-        let mut prev_diag = d[0];
-        d[0] += 1;
-        let mut prev_col = d[0];
-        let src_chr = word1_chars[i - 1];
-        for j in 1..=m {
-            let mut local_cost = prev_diag;
-            let del_cost = d[j];
-            if src_chr != word2_chars[j - 1] {
-                local_cost = usize::min(local_cost, prev_col);
-                local_cost = usize::min(local_cost, del_cost);
-                local_cost += 1;
-            }
-            prev_col = local_cost;
-            d[j] = local_cost;
-            prev_diag = del_cost;
+    py.allow_threads(move || {
+        let word1_chars = char_vec(&word1, case_sensitive, remove_whitespace);
+        let word2_chars = char_vec(&word2, case_sensitive, remove_whitespace);
+        let mut d: Vec<usize> = (0..=word2_chars.len()).collect();
+        for &src_chr in &word1_chars {
+            levenshtein_row(&mut d, src_chr, &word2_chars);
         }
-    }
 
-    return Ok(d[m] as i32);
+        Ok(d[word2_chars.len()] as i32)
+    })
+}
+
+/// damerau_levenshtein_distance(a, b, /, case_sensitive=False)
+/// --
+///
+/// Calculate the Damerau-Levenshtein distance between two strings, where swapping
+/// two adjacent characters counts as a single edit.
+#[pyfunction(
+    case_sensitive = "false",
+    remove_whitespace = "false"
+)]
+pub fn damerau_levenshtein_distance(
+    py: Python,
+    word1: String,
+    word2: String,
+    case_sensitive: bool,
+    remove_whitespace: bool
+) -> PyResult<i32> {
+    py.allow_threads(move || {
+        let word1_chars = char_vec(&word1, case_sensitive, remove_whitespace);
+        let word2_chars = char_vec(&word2, case_sensitive, remove_whitespace);
+        Ok(damerau_levenshtein_distance_target_matched_preprocessed(&word1_chars, &word2_chars, 0.0)? as i32)
+    })
 }
 
 /// jaro_similarity(a, b, /, case_sensitive=False)
@@ -56,11 +66,16 @@ pub fn levenshtein_distance(
     remove_whitespace = "false"
 )]
 pub fn jaro_similarity(
-    word1: &str,
-    word2: &str,
+    py: Python,
+    word1: String,
+    word2: String,
     case_sensitive: bool,
     remove_whitespace: bool
 ) -> PyResult<f32> {
+    py.allow_threads(move || jaro(&word1, &word2, case_sensitive, remove_whitespace))
+}
+
+fn jaro(word1: &str, word2: &str, case_sensitive: bool, remove_whitespace: bool) -> PyResult<f32> {
     let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
     let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
     if word1_chars == word2_chars {
@@ -120,34 +135,35 @@ pub fn jaro_similarity(
     threshold = "0.7"
 )]
 pub fn jaro_winkler_similarity(
-    word1: &str,
-    word2: &str,
+    py: Python,
+    word1: String,
+    word2: String,
     case_sensitive: bool,
     remove_whitespace: bool,
     threshold: f32,
 ) -> PyResult<f32> {
-    if threshold < 0.0 || threshold > 1.0 {
+    if !(0.0..=1.0).contains(&threshold) {
         return Err(PyValueError::new_err(
             "threshold must be between 0.0 and 1.0",
         ));
     }
-    let mut jaro_similarity =
-        jaro_similarity(word1, word2, case_sensitive, remove_whitespace)
-            .expect("Failed to calculate Jaro similarity.");
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
-    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
-    if jaro_similarity > threshold {
-        let mut prefix = 0;
-        for i in 0..usize::min(word1_chars.len(), word2_chars.len()) {
-            if word1_chars[i] != word2_chars[i] {
-                break;
+    py.allow_threads(move || {
+        let mut jaro_similarity = jaro(&word1, &word2, case_sensitive, remove_whitespace)?;
+        let word1_chars = char_vec(&word1, case_sensitive, remove_whitespace);
+        let word2_chars = char_vec(&word2, case_sensitive, remove_whitespace);
+        if jaro_similarity > threshold {
+            let mut prefix = 0;
+            for i in 0..usize::min(word1_chars.len(), word2_chars.len()) {
+                if word1_chars[i] != word2_chars[i] {
+                    break;
+                }
+                prefix += 1;
             }
-            prefix += 1;
+            prefix = i32::min(4, prefix);
+            jaro_similarity += 0.1 * prefix as f32 * (1.0 - jaro_similarity);
         }
-        prefix = i32::min(4, prefix);
-        jaro_similarity += 0.1 * prefix as f32 * (1.0 - jaro_similarity);
-    }
-    Ok(jaro_similarity)
+        Ok(jaro_similarity)
+    })
 }
 
 /// hamming_distance(a, b, /, case_sensitive = False)
@@ -159,23 +175,52 @@ pub fn jaro_winkler_similarity(
     remove_whitespace = "false",
 )]
 pub fn hamming_distance(
-    word1: &str,
-    word2: &str,
+    py: Python,
+    word1: String,
+    word2: String,
     case_sensitive: bool,
     remove_whitespace: bool
 ) -> PyResult<i32> {
-    let word1_chars = char_vec(word1, case_sensitive, remove_whitespace);
-    let word2_chars = char_vec(word2, case_sensitive, remove_whitespace);
-    if word1_chars.len() != word2_chars.len() {
-        return Err(PyValueError::new_err(
-            "Words must be the same length to use Hamming distance",
-        ));
-    }
-    let mut distance = 0;
-    for (i, j) in word1_chars.iter().zip(word2_chars.iter()) {
-        if i != j {
-            distance += 1;
+    py.allow_threads(move || {
+        let word1_chars = char_vec(&word1, case_sensitive, remove_whitespace);
+        let word2_chars = char_vec(&word2, case_sensitive, remove_whitespace);
+        if word1_chars.len() != word2_chars.len() {
+            return Err(PyValueError::new_err(
+                "Words must be the same length to use Hamming distance",
+            ));
         }
-    }
-    Ok(distance)
+        let mut distance = 0;
+        for (i, j) in word1_chars.iter().zip(word2_chars.iter()) {
+            if i != j {
+                distance += 1;
+            }
+        }
+        Ok(distance)
+    })
+}
+
+/// typo_distance(a, b, /, case_sensitive=False, remove_whitespace=False, layout=None)
+/// --
+///
+/// Calculate a Levenshtein distance where substituting a key with one of its neighbors
+/// on the keyboard costs less than a full edit. The layout defaults to QWERTY.
+#[pyfunction(
+    case_sensitive = "false",
+    remove_whitespace = "false",
+    layout = "None"
+)]
+pub fn typo_distance(
+    word1: String,
+    word2: String,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+    layout: Option<PyObject>,
+    py: Python,
+) -> PyResult<f32> {
+    let custom_layout = optional_layout_from_py(py, layout)?;
+    py.allow_threads(move || {
+        let word1_chars = char_vec(&word1, case_sensitive, remove_whitespace);
+        let word2_chars = char_vec(&word2, case_sensitive, remove_whitespace);
+        Ok(typo_distance_with_layout(&word1_chars, &word2_chars, layout_or_qwerty(&custom_layout)))
+    })
 }
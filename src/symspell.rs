@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use crate::internal_scorer::levenshtein_distance_within;
+use crate::parallel::{install, min_len};
+use crate::persist::{check_candidate, check_count, corrupt, load_index, save_index, Persist, Reader, StoredIndex};
+use crate::utils::{char_vec, read_lock, write_lock};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+pub fn deletes(word: &[char], max_distance: usize) -> HashSet<Vec<char>> {
+    let mut found = HashSet::new();
+    found.insert(word.to_vec());
+    let mut frontier = vec![word.to_vec()];
+    // Nothing is left to delete after word.len() rounds, however large max_distance is.
+    for _ in 0..usize::min(max_distance, word.len()) {
+        if frontier.is_empty() {
+            break;
+        }
+        let mut next = Vec::new();
+        for variant in &frontier {
+            for i in 0..variant.len() {
+                let mut deleted = variant.clone();
+                deleted.remove(i);
+                if found.insert(deleted.clone()) {
+                    next.push(deleted);
+                }
+            }
+        }
+        frontier = next;
+    }
+    found
+}
+
+struct SymSpellStore {
+    // Removed words leave a None behind so that the positions of the others do not change.
+    words: Vec<Option<String>>,
+    processed: Vec<Vec<char>>,
+    frequencies: Vec<u64>,
+    deletes: HashMap<Vec<char>, Vec<usize>>,
+    count: usize,
+}
+
+impl SymSpellStore {
+    fn insert(&mut self, index: usize, chars: Vec<char>, max_distance: usize) {
+        for variant in deletes(&chars, max_distance) {
+            self.deletes.entry(variant).or_default().push(index);
+        }
+        self.processed[index] = chars;
+    }
+
+    fn detach(&mut self, index: usize, max_distance: usize) {
+        for variant in deletes(&self.processed[index], max_distance) {
+            if let Some(entries) = self.deletes.get_mut(&variant) {
+                entries.retain(|&i| i != index);
+                if entries.is_empty() {
+                    self.deletes.remove(&variant);
+                }
+            }
+        }
+        self.processed[index] = Vec::new();
+    }
+
+    // Words equal after preprocessing share one position, and every word is listed under itself with nothing deleted.
+    fn position(&self, chars: &[char]) -> Option<usize> {
+        self.deletes
+            .get(chars)
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|&i| self.processed[i] == chars)
+    }
+
+    fn word(&self, index: usize) -> String {
+        self.words[index]
+            .clone()
+            .expect("Deletes only hold words that have not been removed.")
+    }
+
+    fn validate(&self) -> PyResult<()> {
+        if self.processed.len() != self.words.len() || self.frequencies.len() != self.words.len() {
+            return Err(corrupt("words, processed words and frequencies differ in length."));
+        }
+        for &i in self.deletes.values().flatten() {
+            check_candidate(i, &self.words)?;
+        }
+        check_count(self.count, &self.words)
+    }
+
+    fn lookup(&self, query: &[char], max_distance: usize) -> Vec<(usize, usize)> {
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        for variant in deletes(query, max_distance) {
+            for &i in self.deletes.get(&variant).into_iter().flatten() {
+                if !seen.insert(i) {
+                    continue;
+                }
+                if let Some(distance) = levenshtein_distance_within(&self.processed[i], query, max_distance as f32) {
+                    found.push((i, distance as usize));
+                }
+            }
+        }
+        found.sort_unstable_by_key(|&(i, distance)| (distance, std::cmp::Reverse(self.frequencies[i]), i));
+        found
+    }
+}
+
+impl Persist for SymSpellStore {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.words.write(out);
+        self.processed.write(out);
+        self.frequencies.write(out);
+        self.deletes.write(out);
+        self.count.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = SymSpellStore {
+            words: Persist::read(input)?,
+            processed: Persist::read(input)?,
+            frequencies: Persist::read(input)?,
+            deletes: Persist::read(input)?,
+            count: Persist::read(input)?,
+        };
+        store.validate()?;
+        Ok(store)
+    }
+}
+
+/// SymSpell(words, /, max_distance=2, frequencies=None, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// A symmetric-delete spelling correction index. Every deletion of every word up to max_distance
+/// characters is precomputed, so lookups only generate deletions of the query and verify the
+/// dictionary words they lead to with the Levenshtein distance. Words can be added, removed and
+/// updated in place, and lookups from other threads wait for an update to finish.
+#[pyclass]
+pub struct SymSpell {
+    store: RwLock<SymSpellStore>,
+    max_distance: usize,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+}
+
+impl SymSpell {
+    fn processed(&self, word: &str) -> Vec<char> {
+        char_vec(word, self.case_sensitive, self.remove_whitespace)
+    }
+
+    fn position(&self, store: &SymSpellStore, word: &str) -> PyResult<usize> {
+        store
+            .position(&self.processed(word))
+            .ok_or_else(|| PyValueError::new_err(format!("Word not in index: {}", word)))
+    }
+}
+
+impl Persist for SymSpell {
+    fn write(&self, out: &mut Vec<u8>) {
+        read_lock(&self.store).write(out);
+        self.max_distance.write(out);
+        self.case_sensitive.write(out);
+        self.remove_whitespace.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(SymSpell {
+            store: RwLock::new(SymSpellStore::read(input)?),
+            max_distance: Persist::read(input)?,
+            case_sensitive: Persist::read(input)?,
+            remove_whitespace: Persist::read(input)?,
+        })
+    }
+}
+
+impl StoredIndex for SymSpell {
+    const KIND: u32 = 3;
+    const NAME: &'static str = "SymSpell";
+}
+
+#[pymethods]
+impl SymSpell {
+    #[new]
+    #[args(
+        max_distance = "2",
+        frequencies = "None",
+        case_sensitive = "false",
+        remove_whitespace = "false"
+    )]
+    fn new(
+        words: Vec<String>,
+        max_distance: usize,
+        frequencies: Option<Vec<u64>>,
+        case_sensitive: bool,
+        remove_whitespace: bool,
+    ) -> PyResult<Self> {
+        let word_frequencies = match frequencies {
+            Some(frequencies) if frequencies.len() != words.len() => {
+                return Err(PyValueError::new_err(format!(
+                    "frequencies must have one entry per word: expected {}, got {}",
+                    words.len(),
+                    frequencies.len()
+                )))
+            }
+            Some(frequencies) => frequencies,
+            None => vec![1; words.len()],
+        };
+        let mut store = SymSpellStore {
+            words: Vec::new(),
+            processed: Vec::new(),
+            frequencies: Vec::new(),
+            deletes: HashMap::new(),
+            count: 0,
+        };
+        let mut positions: HashMap<Vec<char>, usize> = HashMap::new();
+        for (word, frequency) in words.into_iter().zip(word_frequencies) {
+            let processed = char_vec(&word, case_sensitive, remove_whitespace);
+            match positions.get(&processed) {
+                Some(&i) => store.frequencies[i] = store.frequencies[i].saturating_add(frequency),
+                None => {
+                    positions.insert(processed.clone(), store.words.len());
+                    store.words.push(Some(word));
+                    store.processed.push(processed);
+                    store.frequencies.push(frequency);
+                }
+            }
+        }
+        store.count = store.words.len();
+        let word_deletes = install(|| {
+            store
+                .processed
+                .par_iter()
+                .with_min_len(min_len())
+                .map(|processed| deletes(processed, max_distance))
+                .collect::<Vec<_>>()
+        });
+        for (i, variants) in word_deletes.into_iter().enumerate() {
+            for variant in variants {
+                store.deletes.entry(variant).or_default().push(i);
+            }
+        }
+        Ok(SymSpell { store: RwLock::new(store), max_distance, case_sensitive, remove_whitespace })
+    }
+
+    /// lookup(self, query, /, max_distance=None)
+    /// --
+    ///
+    /// Find every dictionary word within max_distance edits of the query (the index's max_distance
+    /// by default), as (word, distance) pairs ordered by distance, then by descending frequency.
+    #[args(max_distance = "None")]
+    fn lookup(&self, py: Python, query: String, max_distance: Option<usize>) -> PyResult<Vec<(String, f32)>> {
+        let max_distance = max_distance.unwrap_or(self.max_distance);
+        if max_distance > self.max_distance {
+            return Err(PyValueError::new_err(format!(
+                "max_distance must be less than or equal to the index max_distance: {}",
+                self.max_distance
+            )));
+        }
+        Ok(py.allow_threads(|| {
+            install(|| {
+                let processed_query = self.processed(&query);
+                let store = read_lock(&self.store);
+                store
+                    .lookup(&processed_query, max_distance)
+                    .into_iter()
+                    .map(|(i, distance)| (store.word(i), distance as f32))
+                    .collect()
+            })
+        }))
+    }
+
+    /// correct(self, query, /)
+    /// --
+    ///
+    /// Return the closest, most frequent dictionary word within the index's max_distance of the query,
+    /// or None when there is no such word.
+    fn correct(&self, py: Python, query: String) -> Option<String> {
+        py.allow_threads(|| {
+            install(|| {
+                let processed_query = self.processed(&query);
+                let store = read_lock(&self.store);
+                store
+                    .lookup(&processed_query, self.max_distance)
+                    .first()
+                    .map(|&(i, _)| store.word(i))
+            })
+        })
+    }
+
+    /// add(self, word, /, frequency=1)
+    /// --
+    ///
+    /// Add a word with the given frequency. A word already in the index, as compared by lookups,
+    /// has the frequency added to its own instead.
+    #[args(frequency = "1")]
+    fn add(&self, word: String, frequency: u64) {
+        let processed = self.processed(&word);
+        let mut store = write_lock(&self.store);
+        if let Some(i) = store.position(&processed) {
+            store.frequencies[i] = store.frequencies[i].saturating_add(frequency);
+            return;
+        }
+        let index = store.words.len();
+        store.words.push(Some(word));
+        store.processed.push(Vec::new());
+        store.frequencies.push(frequency);
+        store.insert(index, processed, self.max_distance);
+        store.count += 1;
+    }
+
+    /// remove(self, word, /)
+    /// --
+    ///
+    /// Remove a word, as compared by lookups. Raises ValueError when it is not in the index.
+    fn remove(&self, word: &str) -> PyResult<()> {
+        let mut store = write_lock(&self.store);
+        let i = self.position(&store, word)?;
+        store.detach(i, self.max_distance);
+        store.words[i] = None;
+        store.frequencies[i] = 0;
+        store.count -= 1;
+        Ok(())
+    }
+
+    /// update(self, old, new, /)
+    /// --
+    ///
+    /// Replace the old word with the new one, keeping its frequency. When the new word is already in the
+    /// index, the old word's frequency is added to it and the old word is removed.
+    /// Raises ValueError when the old word is not in the index.
+    fn update(&self, old: &str, new: String) -> PyResult<()> {
+        let processed = self.processed(&new);
+        let mut store = write_lock(&self.store);
+        let i = self.position(&store, old)?;
+        match store.position(&processed) {
+            Some(j) if j != i => {
+                store.frequencies[j] = store.frequencies[j].saturating_add(store.frequencies[i]);
+                store.detach(i, self.max_distance);
+                store.words[i] = None;
+                store.frequencies[i] = 0;
+                store.count -= 1;
+            }
+            Some(_) => store.words[i] = Some(new),
+            None => {
+                store.detach(i, self.max_distance);
+                store.insert(i, processed, self.max_distance);
+                store.words[i] = Some(new);
+            }
+        }
+        Ok(())
+    }
+
+    #[getter]
+    fn max_distance(&self) -> usize {
+        self.max_distance
+    }
+
+    /// save(self, path, /)
+    /// --
+    ///
+    /// Write the index to a versioned, checksummed binary file that SymSpell.load can read back without rebuilding it.
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_index(self, path)
+    }
+
+    /// load(path, /)
+    /// --
+    ///
+    /// Load an index written by SymSpell.save.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_index(path)
+    }
+
+    fn __len__(&self) -> usize {
+        read_lock(&self.store).count
+    }
+}
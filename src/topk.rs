@@ -0,0 +1,128 @@
+use std::collections::BinaryHeap;
+
+use ordered_float::OrderedFloat;
+
+/// The n best scores seen so far, in a heap topped by the worst of them so that a better score can replace it
+/// in O(log n). Equal scores are ordered by their tie key and then by index, whatever order they are pushed in.
+pub struct TopK<T: Ord> {
+    heap: BinaryHeap<(OrderedFloat<f32>, T, usize)>,
+    n: usize,
+    distance: bool,
+}
+
+impl<T: Ord> TopK<T> {
+    pub fn new(n: usize, distance: bool) -> TopK<T> {
+        TopK { heap: BinaryHeap::with_capacity(n), n, distance }
+    }
+
+    // Heap keys grow as scores get worse: distances as they are, similarities negated.
+    fn key(&self, score: f32) -> OrderedFloat<f32> {
+        OrderedFloat(if self.distance { score } else { -score })
+    }
+
+    fn score(&self, key: OrderedFloat<f32>) -> f32 {
+        if self.distance {
+            key.0
+        } else {
+            -key.0
+        }
+    }
+
+    pub fn push(&mut self, index: usize, score: f32, tie: T) {
+        self.push_entry((self.key(score), tie, index));
+    }
+
+    fn push_entry(&mut self, entry: (OrderedFloat<f32>, T, usize)) {
+        if self.heap.len() < self.n {
+            self.heap.push(entry);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if entry < *worst {
+                *worst = entry;
+            }
+        }
+    }
+
+    /// The worst score kept once n are, which any other option has to match to get in.
+    pub fn bound(&self) -> Option<f32> {
+        if self.heap.len() < self.n {
+            return None;
+        }
+        self.heap.peek().map(|(key, _, _)| self.score(*key))
+    }
+
+    pub fn merge(self, other: TopK<T>) -> TopK<T> {
+        let (mut larger, smaller) = if self.heap.len() >= other.heap.len() { (self, other) } else { (other, self) };
+        for entry in smaller.heap {
+            larger.push_entry(entry);
+        }
+        larger
+    }
+
+    /// The (index, score) pairs kept, best first.
+    pub fn into_sorted(mut self) -> Vec<(usize, f32)> {
+        std::mem::take(&mut self.heap)
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(key, _, index)| (index, self.score(key)))
+            .collect()
+    }
+}
+
+/// The best score seen so far and every index that has it.
+pub struct BestTies {
+    best: Option<(OrderedFloat<f32>, Vec<usize>)>,
+    distance: bool,
+}
+
+impl BestTies {
+    pub fn new(distance: bool) -> BestTies {
+        BestTies { best: None, distance }
+    }
+
+    fn key(&self, score: f32) -> OrderedFloat<f32> {
+        OrderedFloat(if self.distance { score } else { -score })
+    }
+
+    fn score(&self, key: OrderedFloat<f32>) -> f32 {
+        if self.distance {
+            key.0
+        } else {
+            -key.0
+        }
+    }
+
+    pub fn push(&mut self, index: usize, score: f32) {
+        self.push_all(self.key(score), vec![index]);
+    }
+
+    fn push_all(&mut self, key: OrderedFloat<f32>, indices: Vec<usize>) {
+        match &mut self.best {
+            Some((best_key, best_indices)) if key == *best_key => best_indices.extend(indices),
+            Some((best_key, _)) if key > *best_key => {}
+            _ => self.best = Some((key, indices)),
+        }
+    }
+
+    /// The best score so far, which an option has to match to be kept.
+    pub fn bound(&self) -> Option<f32> {
+        self.best.as_ref().map(|(key, _)| self.score(*key))
+    }
+
+    pub fn merge(mut self, other: BestTies) -> BestTies {
+        if let Some((key, indices)) = other.best {
+            self.push_all(key, indices);
+        }
+        self
+    }
+
+    /// The (index, score) pairs tied for the best score, in index order.
+    pub fn into_sorted(mut self) -> Vec<(usize, f32)> {
+        match self.best.take() {
+            Some((key, mut indices)) => {
+                indices.sort_unstable();
+                indices.into_iter().map(|index| (index, self.score(key))).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+}
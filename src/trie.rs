@@ -0,0 +1,364 @@
+use std::sync::RwLock;
+
+use crate::internal_scorer::levenshtein_row;
+use crate::parallel::install;
+use crate::persist::{
+    check_candidate, check_count, check_position, corrupt, load_index, save_index, Persist, Reader, StoredIndex,
+};
+use crate::utils::{candidate_positions, char_vec, read_lock, write_lock};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rayon::prelude::*;
+
+#[derive(Default)]
+struct TrieNode {
+    children: Vec<(char, usize)>,
+    entries: Vec<usize>,
+}
+
+impl Persist for TrieNode {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.children.write(out);
+        self.entries.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(TrieNode { children: Persist::read(input)?, entries: Persist::read(input)? })
+    }
+}
+
+fn next_row(row: &[usize], chr: char, query: &[char]) -> Vec<usize> {
+    let mut next = row.to_vec();
+    levenshtein_row(&mut next, chr, query);
+    next
+}
+
+struct TrieStore {
+    // Removed candidates leave a None behind so that the positions of the others do not change.
+    candidates: Vec<Option<String>>,
+    nodes: Vec<TrieNode>,
+    // The length of the longest candidate ever inserted, an upper bound on the distances to search.
+    max_depth: usize,
+    count: usize,
+}
+
+impl TrieStore {
+    fn insert(&mut self, index: usize, chars: &[char]) {
+        let mut node = 0;
+        for &chr in chars {
+            node = match self.nodes[node].children.binary_search_by_key(&chr, |&(c, _)| c) {
+                Ok(position) => self.nodes[node].children[position].1,
+                Err(position) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children.insert(position, (chr, child));
+                    child
+                }
+            };
+        }
+        self.nodes[node].entries.push(index);
+        self.max_depth = usize::max(self.max_depth, chars.len());
+    }
+
+    // The nodes along the path are kept, an empty branch only costs a little time in searches.
+    fn detach(&mut self, index: usize, chars: &[char]) {
+        let mut node = 0;
+        for &chr in chars {
+            match self.nodes[node].children.binary_search_by_key(&chr, |&(c, _)| c) {
+                Ok(position) => node = self.nodes[node].children[position].1,
+                Err(_) => return,
+            }
+        }
+        self.nodes[node].entries.retain(|&i| i != index);
+    }
+
+    fn candidate(&self, index: usize) -> String {
+        self.candidates[index]
+            .clone()
+            .expect("Trie nodes only hold candidates that have not been removed.")
+    }
+
+    // Nodes are always created after their parent, so a child before its parent would mean a cycle.
+    fn validate(&self) -> PyResult<()> {
+        if self.nodes.is_empty() {
+            return Err(corrupt("the trie has no root node."));
+        }
+        for (position, node) in self.nodes.iter().enumerate() {
+            for &i in &node.entries {
+                check_candidate(i, &self.candidates)?;
+            }
+            for &(_, child) in &node.children {
+                check_position(child, self.nodes.len(), "node")?;
+                if child <= position {
+                    return Err(corrupt(&format!("node {} is a child of node {}.", child, position)));
+                }
+            }
+        }
+        check_count(self.count, &self.candidates)
+    }
+
+    // Each DP row is the state of the query's Levenshtein automaton after reading the prefix spelled by
+    // the path to the node, and the walk goes no deeper once every cell exceeds max_distance. Nodes still
+    // to visit wait on a stack with their rows, so a long candidate cannot overflow a worker thread's stack.
+    fn walk(&self, node: usize, row: Vec<usize>, query: &[char], max_distance: usize, found: &mut Vec<(usize, usize)>) {
+        let mut stack = vec![(node, row)];
+        while let Some((node, row)) = stack.pop() {
+            let distance = row[query.len()];
+            if distance <= max_distance {
+                found.extend(self.nodes[node].entries.iter().map(|&i| (i, distance)));
+            }
+            if row.iter().min().is_some_and(|&best| best <= max_distance) {
+                for &(child_chr, child) in &self.nodes[node].children {
+                    stack.push((child, next_row(&row, child_chr, query)));
+                }
+            }
+        }
+    }
+
+    pub fn within(&self, query: &[char], max_distance: usize) -> Vec<(usize, usize)> {
+        let first_row = (0..=query.len()).collect::<Vec<_>>();
+        let mut found = Vec::new();
+        if query.len() <= max_distance {
+            found.extend(self.nodes[0].entries.iter().map(|&i| (i, query.len())));
+        }
+        found.extend(install(|| {
+            self.nodes[0]
+                .children
+                .par_iter()
+                .flat_map_iter(|&(chr, child)| {
+                    let mut child_found = Vec::new();
+                    self.walk(child, next_row(&first_row, chr, query), query, max_distance, &mut child_found);
+                    child_found
+                })
+                .collect::<Vec<_>>()
+        }));
+        found.sort_unstable_by_key(|&(i, distance)| (distance, i));
+        found
+    }
+
+    fn n_nearest(&self, query: &[char], n: usize) -> Vec<(usize, usize)> {
+        let max_distance = usize::max(self.max_depth, query.len());
+        let mut distance = 0;
+        loop {
+            let mut found = self.within(query, distance);
+            if found.len() >= n || distance >= max_distance {
+                found.truncate(n);
+                return found;
+            }
+            distance = usize::min(max_distance, usize::max(distance * 2, 1));
+        }
+    }
+}
+
+impl Persist for TrieStore {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.candidates.write(out);
+        self.nodes.write(out);
+        self.max_depth.write(out);
+        self.count.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        let store = TrieStore {
+            candidates: Persist::read(input)?,
+            nodes: Persist::read(input)?,
+            max_depth: Persist::read(input)?,
+            count: Persist::read(input)?,
+        };
+        store.validate()?;
+        Ok(store)
+    }
+}
+
+/// Trie(candidates, /, case_sensitive=False, remove_whitespace=False)
+/// --
+///
+/// The candidates stored in a trie and searched by walking it in step with the Levenshtein
+/// automaton of the query, so that only prefixes that can still be within the distance are visited.
+/// Candidates can be added, removed and updated without rebuilding the trie.
+#[pyclass]
+pub struct Trie {
+    store: RwLock<TrieStore>,
+    case_sensitive: bool,
+    remove_whitespace: bool,
+}
+
+impl Trie {
+    fn n_best(&self, target: &str, n: usize) -> PyResult<Vec<(String, f32)>> {
+        let store = read_lock(&self.store);
+        if store.count == 0 {
+            return Err(PyValueError::new_err("No options provided."));
+        }
+        if n < 1 {
+            return Err(PyValueError::new_err("n must be greater than 0."));
+        }
+        else if n > store.count {
+            return Err(PyValueError::new_err(format!(
+                "n must be less than or equal to the number of options: {}",
+                store.count
+            )));
+        }
+        let processed_target = char_vec(target, self.case_sensitive, self.remove_whitespace);
+        Ok(store
+            .n_nearest(&processed_target, n)
+            .into_iter()
+            .map(|(i, distance)| (store.candidate(i), distance as f32))
+            .collect())
+    }
+}
+
+impl Persist for Trie {
+    fn write(&self, out: &mut Vec<u8>) {
+        read_lock(&self.store).write(out);
+        self.case_sensitive.write(out);
+        self.remove_whitespace.write(out);
+    }
+
+    fn read(input: &mut Reader) -> PyResult<Self> {
+        Ok(Trie {
+            store: RwLock::new(TrieStore::read(input)?),
+            case_sensitive: Persist::read(input)?,
+            remove_whitespace: Persist::read(input)?,
+        })
+    }
+}
+
+impl StoredIndex for Trie {
+    const KIND: u32 = 4;
+    const NAME: &'static str = "Trie";
+}
+
+#[pymethods]
+impl Trie {
+    #[new]
+    #[args(case_sensitive = "false", remove_whitespace = "false")]
+    fn new(candidates: Vec<String>, case_sensitive: bool, remove_whitespace: bool) -> Self {
+        let mut store = TrieStore {
+            candidates: Vec::new(),
+            nodes: vec![TrieNode::default()],
+            max_depth: 0,
+            count: candidates.len(),
+        };
+        for (index, candidate) in candidates.iter().enumerate() {
+            store.insert(index, &char_vec(candidate, case_sensitive, remove_whitespace));
+        }
+        store.candidates = candidates.into_iter().map(Some).collect();
+        Trie { store: RwLock::new(store), case_sensitive, remove_whitespace }
+    }
+
+    /// search(self, query, max_distance, /)
+    /// --
+    ///
+    /// Find every candidate within max_distance Levenshtein edits of the query, as (candidate, distance)
+    /// pairs ordered by distance, then by position in the candidates.
+    fn search(&self, py: Python, query: String, max_distance: usize) -> Vec<(String, f32)> {
+        py.allow_threads(|| {
+            install(|| {
+                let processed_query = char_vec(&query, self.case_sensitive, self.remove_whitespace);
+                let store = read_lock(&self.store);
+                store
+                    .within(&processed_query, max_distance)
+                    .into_iter()
+                    .map(|(i, distance)| (store.candidate(i), distance as f32))
+                    .collect()
+            })
+        })
+    }
+
+    /// closest(self, target, /)
+    /// --
+    ///
+    /// Find the candidate with the smallest Levenshtein distance to the target.
+    fn closest(&self, py: Python, target: String) -> PyResult<String> {
+        py.allow_threads(|| install(|| Ok(self.n_best(&target, 1)?.remove(0).0)))
+    }
+
+    /// closest_with_score(self, target, /)
+    /// --
+    ///
+    /// Find the candidate with the smallest Levenshtein distance to the target and the distance.
+    fn closest_with_score(&self, py: Python, target: String) -> PyResult<(String, f32)> {
+        py.allow_threads(|| install(|| Ok(self.n_best(&target, 1)?.remove(0))))
+    }
+
+    /// n_closest(self, target, n, /)
+    /// --
+    ///
+    /// Find the n candidates with the smallest Levenshtein distances to the target.
+    fn n_closest(&self, py: Python, target: String, n: usize) -> PyResult<Vec<String>> {
+        py.allow_threads(|| install(|| Ok(self.n_best(&target, n)?.into_iter().map(|(candidate, _)| candidate).collect())))
+    }
+
+    /// n_closest_with_score(self, target, n, /)
+    /// --
+    ///
+    /// Find the n candidates with the smallest Levenshtein distances to the target and their distances.
+    fn n_closest_with_score(&self, py: Python, target: String, n: usize) -> PyResult<Vec<(String, f32)>> {
+        py.allow_threads(|| install(|| self.n_best(&target, n)))
+    }
+
+    /// add(self, candidate, /)
+    /// --
+    ///
+    /// Add a candidate after the existing ones.
+    fn add(&self, candidate: String) {
+        let processed = char_vec(&candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        let index = store.candidates.len();
+        store.insert(index, &processed);
+        store.candidates.push(Some(candidate));
+        store.count += 1;
+    }
+
+    /// remove(self, candidate, /)
+    /// --
+    ///
+    /// Remove every copy of the candidate. Raises ValueError when it is not in the trie.
+    fn remove(&self, candidate: &str) -> PyResult<()> {
+        let processed = char_vec(candidate, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, candidate)? {
+            store.detach(i, &processed);
+            store.candidates[i] = None;
+            store.count -= 1;
+        }
+        Ok(())
+    }
+
+    /// update(self, old, new, /)
+    /// --
+    ///
+    /// Replace every copy of the old candidate with the new one, keeping their positions.
+    /// Raises ValueError when the old candidate is not in the trie.
+    fn update(&self, old: &str, new: String) -> PyResult<()> {
+        let old_processed = char_vec(old, self.case_sensitive, self.remove_whitespace);
+        let new_processed = char_vec(&new, self.case_sensitive, self.remove_whitespace);
+        let mut store = write_lock(&self.store);
+        for i in candidate_positions(&store.candidates, old)? {
+            store.detach(i, &old_processed);
+            store.insert(i, &new_processed);
+            store.candidates[i] = Some(new.clone());
+        }
+        Ok(())
+    }
+
+    /// save(self, path, /)
+    /// --
+    ///
+    /// Write the trie to a versioned, checksummed binary file that Trie.load can read back without rebuilding it.
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_index(self, path)
+    }
+
+    /// load(path, /)
+    /// --
+    ///
+    /// Load a trie written by Trie.save.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_index(path)
+    }
+
+    fn __len__(&self) -> usize {
+        read_lock(&self.store).count
+    }
+}
@@ -1,14 +1,56 @@
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use pyo3::{exceptions::PyValueError, PyResult};
+
 pub fn char_vec(word: &str, case_sensitive: bool, remove_whitespace: bool) -> Vec<char> {
     if !remove_whitespace {
         if case_sensitive {
             return word.chars().collect::<Vec<_>>();
         }
-        return word.to_lowercase().chars().collect::<Vec<_>>();
+        word.to_lowercase().chars().collect::<Vec<_>>()
     }
     else {
         if case_sensitive {
             return word.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
         }
-        return word.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+        word.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>()
     }
 }
+
+// Index updates validate their input before changing anything and do not panic part way through,
+// so the data behind a poisoned lock is still consistent.
+pub fn read_lock<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn write_lock<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A stored candidate, or None for one removed from an index that keeps tombstones.
+pub trait StoredCandidate {
+    fn candidate(&self) -> Option<&str>;
+}
+
+impl StoredCandidate for String {
+    fn candidate(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl StoredCandidate for Option<String> {
+    fn candidate(&self) -> Option<&str> {
+        self.as_deref()
+    }
+}
+
+/// The positions of every copy of a candidate in an index.
+pub fn candidate_positions<C: StoredCandidate>(candidates: &[C], candidate: &str) -> PyResult<Vec<usize>> {
+    let positions = (0..candidates.len())
+        .filter(|&i| candidates[i].candidate() == Some(candidate))
+        .collect::<Vec<_>>();
+    if positions.is_empty() {
+        return Err(PyValueError::new_err(format!("Candidate not in index: {}", candidate)));
+    }
+    Ok(positions)
+}
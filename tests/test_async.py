@@ -0,0 +1,66 @@
+import asyncio
+import faulthandler
+import unittest
+
+from ffzf import closest, n_closest, cdist, closest_async, n_closest_async, cdist_async, dedupe, Index
+
+
+class TestAsyncFunctions(unittest.IsolatedAsyncioTestCase):
+
+    async def test_closest_async(self):
+        self.assertEqual(await closest_async("hello", ["world", "hello"]), "hello")
+        self.assertEqual(await closest_async("travel", ["gravel", "gambit", "gated"], algorithm="jaro"), "gravel")
+        with self.assertRaises(ValueError):
+            await closest_async("travel", ["gravel", "gambit"], algorithm="unknown")
+        with self.assertRaises(ValueError):
+            await closest_async("travel", [])
+
+    async def test_n_closest_async(self):
+        options = ["yello", "jello", "harps", "languid"]
+        self.assertEqual(await n_closest_async("hello", options, 3), n_closest("hello", options, 3))
+        with self.assertRaises(ValueError):
+            await n_closest_async("hello", options, 0)
+
+    async def test_cdist_async(self):
+        queries, choices = ["hello", "world"], ["jello", "word", "help"]
+        self.assertEqual([list(row) for row in await cdist_async(queries, choices)],
+                         [list(row) for row in cdist(queries, choices)])
+        with self.assertRaises(ValueError):
+            await cdist_async(["travel"], ["gravel", "gated"], algorithm="hamming")
+
+    async def test_gather(self):
+        options = ["word%d" % i for i in range(5000)]
+        targets = ["word%d" % i for i in range(0, 5000, 500)]
+        results = await asyncio.gather(*(closest_async(target, options) for target in targets))
+        self.assertEqual(results, [closest(target, options) for target in targets])
+
+    async def test_cancel(self):
+        future = cdist_async(["word%d" % i for i in range(200)], ["word%d" % i for i in range(2000)])
+        future.cancel()
+        with self.assertRaises(asyncio.CancelledError):
+            await future
+        self.assertEqual(await closest_async("hello", ["hello"]), "hello")
+
+    async def test_sync_call_while_pending(self):
+        # A synchronous call keeps the GIL while it waits for the thread pool, so the jobs finishing on the
+        # pool meanwhile must not need the GIL. A deadlock here is reported by faulthandler instead of hanging.
+        faulthandler.dump_traceback_later(60, exit=True)
+        try:
+            options = ["word%d" % i for i in range(10000)]
+            targets = ["word%d" % i for i in range(0, 10000, 1000)]
+            index = Index(options)
+            futures = [closest_async(target, options) for target in targets]
+            for target in targets:
+                self.assertEqual(index.closest(target), target)
+            self.assertEqual(dedupe(options[:1000], cutoff=0), [])
+            self.assertEqual(await asyncio.gather(*futures), targets)
+        finally:
+            faulthandler.cancel_dump_traceback_later()
+
+    def test_requires_running_loop(self):
+        with self.assertRaises(RuntimeError):
+            closest_async("hello", ["hello"])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,54 @@
+import unittest
+
+from ffzf import dedupe, cluster
+
+
+class TestClusteringFunctions(unittest.TestCase):
+
+    def test_dedupe(self):
+        names = ["Acme Inc", "ACME Inc.", "Globex", "Acme  Inc", "Globex Corp", "Initech"]
+        self.assertEqual(dedupe(names), [("Acme Inc", ["Acme Inc", "ACME Inc.", "Acme  Inc"])])
+        self.assertEqual(dedupe(names, canonical="longest"), [
+                         ("ACME Inc.", ["Acme Inc", "ACME Inc.", "Acme  Inc"])])
+        self.assertEqual(dedupe(names, canonical="shortest"), [
+                         ("Acme Inc", ["Acme Inc", "ACME Inc.", "Acme  Inc"])])
+        self.assertEqual(dedupe(names, cutoff=5), [
+                         ("Acme Inc", ["Acme Inc", "ACME Inc.", "Acme  Inc"]),
+                         ("Globex", ["Globex", "Globex Corp"])])
+        self.assertEqual(dedupe(names, algorithm="jarowinkler", cutoff=0.9, canonical="central"), [
+                         ("Acme Inc", ["Acme Inc", "ACME Inc.", "Acme  Inc"]),
+                         ("Globex", ["Globex", "Globex Corp"])])
+        self.assertEqual(dedupe(["abc", "abd", "ab"], algorithm="hamming"), [("abc", ["abc", "abd"])])
+        self.assertEqual(dedupe(["hello", "world"]), [])
+        self.assertEqual(dedupe([]), [])
+        self.assertEqual(dedupe(["a", "s", "z"], algorithm="typo", cutoff=0.5), [("a", ["a", "s"])])
+        self.assertEqual(dedupe(["a", "s", "z"], algorithm="typo", cutoff=0.5, layout="azerty"), [("a", ["a", "z"])])
+        with self.assertRaises(ValueError):
+            dedupe(names, algorithm="unknown")
+        with self.assertRaises(ValueError):
+            dedupe(names, canonical="unknown")
+
+    def test_cluster(self):
+        names = ["Acme Inc", "Globex", "ACME Inc.", "Initech", "Globex Corp", "Acme  Inc"]
+        self.assertEqual(cluster(names), [0, 1, 0, 2, 3, 0])
+        self.assertEqual(cluster(names, cutoff=5), [0, 1, 0, 2, 1, 0])
+        self.assertEqual(cluster(names, algorithm="jaro", cutoff=0.85), [0, 1, 0, 2, 3, 0])
+        self.assertEqual(cluster(["abc", "abd", "abe", "xbe"]), [0, 0, 0, 0])
+        self.assertEqual(cluster(["abc", "abd", "abe", "xbe"], method="single"), [0, 0, 0, 0])
+        self.assertEqual(cluster(["abc", "abd", "abe", "xbe"], method="leader"), [0, 0, 0, 1])
+        self.assertEqual(cluster(["abc", "xyz", "abd"], algorithm="hamming", method="leader"), [0, 1, 0])
+        self.assertEqual(cluster([]), [])
+        self.assertEqual(cluster(["a", "s", "z"], algorithm="typo", cutoff=0.5), [0, 0, 1])
+        self.assertEqual(cluster(["a", "s", "z"], algorithm="typo", cutoff=0.5, layout="azerty"), [0, 1, 0])
+        self.assertEqual(cluster(["a", "s", "z"], algorithm="typo", cutoff=0.5, method="leader", layout="azerty"),
+                         [0, 1, 0])
+        with self.assertRaises(ValueError):
+            cluster(names, algorithm="typo", layout="colemak")
+        with self.assertRaises(ValueError):
+            cluster(names, algorithm="unknown")
+        with self.assertRaises(ValueError):
+            cluster(names, method="unknown")
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,95 @@
+import unittest
+
+from ffzf import (
+    editops,
+    opcodes,
+    matching_blocks,
+    alignment,
+    format_alignment,
+    apply_editops,
+    inverse_editops,
+    closest
+)
+
+
+class TestEditOperationFunctions(unittest.TestCase):
+
+    def test_editops(self):
+        self.assertEqual(editops("", ""), [])
+        self.assertEqual(editops("hello", "hello"), [])
+        self.assertEqual(editops("hello", "jello"), [("replace", 0, 0)])
+        self.assertEqual(editops("hello", "hell"), [("delete", 4, 4)])
+        self.assertEqual(editops("hell", "hello"), [("insert", 4, 4)])
+        self.assertEqual(editops("Hello", "hello"), [])
+        self.assertEqual(editops("Hello", "hello", case_sensitive=True), [("replace", 0, 0)])
+        self.assertEqual(editops("spam", "park"), [
+                         ("delete", 0, 0), ("insert", 3, 2), ("replace", 3, 3)])
+        self.assertEqual(len(editops("euphoria", "elation")), 7)
+
+    def test_opcodes(self):
+        self.assertEqual(opcodes("", ""), [])
+        self.assertEqual(opcodes("hello", "hello"), [("equal", 0, 5, 0, 5)])
+        self.assertEqual(opcodes("hello", "jello"), [
+                         ("replace", 0, 1, 0, 1), ("equal", 1, 5, 1, 5)])
+        self.assertEqual(opcodes("spam", "park"), [
+                         ("delete", 0, 1, 0, 0), ("equal", 1, 3, 0, 2),
+                         ("insert", 3, 3, 2, 3), ("replace", 3, 4, 3, 4)])
+        self.assertEqual(opcodes("kitten", "sitting"), [
+                         ("replace", 0, 1, 0, 1), ("equal", 1, 4, 1, 4), ("replace", 4, 5, 4, 5),
+                         ("equal", 5, 6, 5, 6), ("insert", 6, 6, 6, 7)])
+
+    def test_matching_blocks(self):
+        self.assertEqual(matching_blocks("", ""), [(0, 0, 0)])
+        self.assertEqual(matching_blocks("spam", "park"), [(1, 0, 2), (4, 4, 0)])
+        self.assertEqual(matching_blocks("kitten", "sitting"), [(1, 1, 3), (5, 5, 1), (6, 7, 0)])
+        self.assertEqual(matching_blocks("kitten", "sitting", algorithm="lcs"), [
+                         (1, 1, 3), (5, 5, 1), (6, 7, 0)])
+        with self.assertRaises(ValueError):
+            matching_blocks("kitten", "sitting", algorithm="jaro")
+
+    def test_alignment(self):
+        self.assertEqual(alignment("spam", "park"), ("spa-m", " || .", "-park"))
+        self.assertEqual(alignment("spam", "park", algorithm="lcs"), ("spa--m", " ||   ", "-park-"))
+        self.assertEqual(alignment("Kitten", "sitting", gap="_"), ("Kitten_", ".|||.| ", "sitting"))
+        self.assertEqual(format_alignment("spam", "park"), "spa-m\n || .\n-park")
+        with self.assertRaises(ValueError):
+            alignment("spam", "park", algorithm="hamming")
+        with self.assertRaises(ValueError):
+            closest("spam", ["park"], algorithm="lcs")
+
+    def test_apply_editops(self):
+        ops = editops("spam", "park")
+        self.assertEqual(apply_editops("spam", ops, "park"), "park")
+        self.assertEqual(apply_editops("spam", [], "park"), "spam")
+        self.assertEqual(apply_editops("", editops("", "abc"), "abc"), "abc")
+        self.assertEqual(apply_editops("abc", editops("abc", ""), ""), "")
+        ops = editops("new yrok CITY", "New York City")
+        self.assertEqual(apply_editops("new yrok CITY", ops, "New York City"), "new york CITY")
+        ops = editops("new yrok CITY", "NewYork City", remove_whitespace=True)
+        self.assertEqual(apply_editops("new yrok CITY", ops, "NewYork City",
+                         remove_whitespace=True), "new york CITY")
+        self.assertEqual(apply_editops("NewYork City", inverse_editops(ops), "new yrok CITY",
+                         remove_whitespace=True), "NewYrok City")
+        ops = editops("a bc", "xa c", case_sensitive=True, remove_whitespace=True)
+        self.assertEqual(apply_editops("a bc", ops, "xa c", case_sensitive=True,
+                         remove_whitespace=True), "x ac")
+        ops = editops("İx", "iy")
+        self.assertEqual(apply_editops("İx", ops, "iy"), "iy")
+        self.assertEqual(apply_editops("Straße", editops("Straße", "strasse"), "strasse"), "Strasse")
+        with self.assertRaises(ValueError):
+            apply_editops("spam", [("swap", 0, 0)], "park")
+        with self.assertRaises(ValueError):
+            apply_editops("spam", [("replace", 3, 3), ("replace", 0, 0)], "park")
+        with self.assertRaises(ValueError):
+            apply_editops("spam", [("replace", 10, 0)], "park")
+
+    def test_inverse_editops(self):
+        ops = editops("spam", "park")
+        self.assertEqual(inverse_editops(ops), [
+                         ("insert", 0, 0), ("delete", 2, 3), ("replace", 3, 3)])
+        self.assertEqual(apply_editops("park", inverse_editops(ops), "spam"), "spam")
+        self.assertEqual(inverse_editops(inverse_editops(ops)), ops)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -1,6 +1,11 @@
+import random
+import threading
+import time
 import unittest
 
-from ffzf import closest, n_closest, closest_index_pair, closest_with_score, n_closest_with_score
+from ffzf import closest, n_closest, closest_index_pair, closest_with_score, n_closest_with_score, cdist, closest_all, \
+    closest_many, n_closest_many, set_num_threads, get_num_threads, set_parallel_threshold, get_parallel_threshold, \
+    dedupe, cluster, fuzzy_join, Trie, QGramIndex
 
 
 class TestFindingFunctions(unittest.TestCase):
@@ -15,6 +20,12 @@ class TestFindingFunctions(unittest.TestCase):
             "travel", ["gravel", "gambit", "gated"], algorithm="jarowinkler"), "gravel")
         self.assertEqual(closest(
             "travel", ["gravel", "gambit", "guards"], algorithm="hamming"), "gravel")
+        self.assertEqual(closest(
+            "hrllo", ["hallo", "hello", "hullo"], algorithm="typo"), "hello")
+        self.assertEqual(closest("a", ["s", "z"], algorithm="typo"), "s")
+        self.assertEqual(closest("a", ["s", "z"], algorithm="typo", layout="azerty"), "z")
+        with self.assertRaises(ValueError):
+            closest("a", ["s", "z"], algorithm="typo", layout="colemak")
         with self.assertRaises(ValueError):
             closest("travel", ["gravel", "gambit",
                     "gated"], algorithm="unknown")
@@ -60,12 +71,201 @@ class TestFindingFunctions(unittest.TestCase):
             closest_index_pair(
                 "travel", "gravel gambit gated", algorithm="unknown")
 
+    def test_closest_index_pair_characters(self):
+        self.assertEqual(closest_index_pair("café", "un café noir"), (3, 7))
+        self.assertEqual(closest_index_pair("noir", "un café noir"), (8, 12))
+        self.assertEqual(closest_index_pair("", "hello"), (0, 0))
+        with self.assertRaises(ValueError):
+            closest_index_pair("hello world", "hello")
+
+    def test_invalid_threshold(self):
+        options = ["word%d" % i for i in range(3000)]
+        for finder in [closest, closest_with_score, closest_all]:
+            with self.assertRaises(ValueError):
+                finder("word1", options, algorithm="jarowinkler", threshold=1.5)
+        for finder in [n_closest, n_closest_with_score]:
+            with self.assertRaises(ValueError):
+                finder("word1", options, n=3, algorithm="jarowinkler", threshold=-0.5)
+        with self.assertRaises(ValueError):
+            closest_many(["word1"], options, algorithm="jarowinkler", threshold=2.0)
+        with self.assertRaises(ValueError):
+            cdist(["word1"], options, algorithm="jarowinkler", threshold=2.0)
+        with self.assertRaises(ValueError):
+            closest_index_pair("word", "a word here", algorithm="jarowinkler", threshold=2.0)
+        self.assertEqual(closest("word1", options, algorithm="jarowinkler", threshold=1.0), "word1")
+
     def test_closest_with_score(self):
         self.assertEqual(closest_with_score("euphoria", ["excitement", "elation", "joyful"]), ("elation", 7))
     
     def test_n_closest_with_score(self):
         self.assertEqual(n_closest_with_score("euphoria", ["excitement", "elation", "joyful"], n=2), [("elation", 7), ("joyful", 8)])
 
+    def test_n_closest_with_score_filtered(self):
+        options = ["a" * 40, "ab", "zzzz", "abcd", "dcba", "abcde" * 8, "bacd"]
+        self.assertEqual(n_closest_with_score("abcd", options, n=2, algorithm="damerau"), [("abcd", 0), ("bacd", 1)])
+        self.assertEqual(n_closest_with_score("abcd", options, n=3), [("abcd", 0), ("ab", 2), ("bacd", 2)])
+        self.assertEqual(n_closest("abcde" * 8, options, n=1), ["abcde" * 8])
+
+    def test_n_closest_top_k(self):
+        rng = random.Random(7)
+        options = ["".join(rng.choice("abcdeqwsz") for _ in range(rng.randint(0, 10))) for _ in range(2500)]
+        for algorithm in ["levenshtein", "damerau", "typo", "jaro", "jarowinkler"]:
+            for n in [1, 5, 40, len(options)]:
+                row = list(cdist(["abcdeq"], options, algorithm=algorithm)[0])
+                expected = sorted(row, reverse=algorithm.startswith("jaro"))[:n]
+                scores = [score for _, score in n_closest_with_score("abcdeq", options, n=n, algorithm=algorithm)]
+                self.assertEqual([round(score, 4) for score in scores], [round(score, 4) for score in expected])
+
+    def test_tie_break(self):
+        options = ["bbbx", "abc", "bxb", "aaa", "xbb", "bb"]
+        self.assertEqual(closest("bbb", options), "bbbx")
+        self.assertEqual(closest("bbb", options, tie_break="shortest"), "bb")
+        self.assertEqual(closest("bbb", options, tie_break="lexicographic"), "bb")
+        self.assertEqual(closest_with_score("bbb", options, tie_break="shortest"), ("bb", 1))
+        self.assertEqual(n_closest("bbb", options, n=5), ["bbbx", "bxb", "xbb", "bb", "abc"])
+        self.assertEqual(n_closest("bbb", options, n=4, tie_break="shortest"), ["bb", "bxb", "xbb", "bbbx"])
+        self.assertEqual(n_closest_with_score("bbb", options, n=3, tie_break="lexicographic"),
+                         [("bb", 1), ("bbbx", 1), ("bxb", 1)])
+        self.assertEqual(closest("hello", ["hallo", "hullo"], algorithm="jaro"), "hallo")
+        with self.assertRaises(ValueError):
+            closest("bbb", options, tie_break="random")
+
+        words = ["word%d" % i for i in range(5000)]
+        expected = [("word1", 0)] + [("word%d" % i, 1) for i in [0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]]
+        set_parallel_threshold(0)
+        try:
+            for _ in range(5):
+                self.assertEqual(n_closest_with_score("word1", words, n=12), expected)
+        finally:
+            set_parallel_threshold(1000)
+
+    def test_closest_all(self):
+        options = ["bbbx", "abc", "bxb", "aaa", "xbb", "bb"]
+        self.assertEqual(closest_all("bbb", options), ["bbbx", "bxb", "xbb", "bb"])
+        self.assertEqual(closest_all("bbb", options, tie_break="shortest"), ["bb", "bxb", "xbb", "bbbx"])
+        self.assertEqual(closest_all("bbb", options, tie_break="lexicographic"), ["bb", "bbbx", "bxb", "xbb"])
+        self.assertEqual(closest_all("aaa", options), ["aaa"])
+        self.assertEqual(closest_all("hello", ["hallo", "hullo", "help"], algorithm="jaro"), ["hallo", "hullo"])
+        self.assertEqual(closest_all("word1", ["word%d" % i for i in range(3000)] * 2), ["word1", "word1"])
+        with self.assertRaises(ValueError):
+            closest_all("bbb", [])
+        with self.assertRaises(ValueError):
+            closest_all("bbb", options, algorithm="hamming")
+
+    def test_closest_many(self):
+        self.assertEqual(closest_many(["hello", "world", "travel"], ["jello", "word", "gravel"]), [
+                         "jello", "word", "gravel"])
+        self.assertEqual(closest_many(["travel", "gambol"], ["gravel", "gambit", "gated"], algorithm="jaro"), [
+                         "gravel", "gambit"])
+        self.assertEqual(closest_many([], ["hello"]), [])
+        with self.assertRaises(ValueError):
+            closest_many(["travel"], [])
+        with self.assertRaises(ValueError):
+            closest_many(["travel"], ["gravel", "gated"], algorithm="hamming")
+
+    def test_n_closest_many(self):
+        self.assertEqual(n_closest_many(["hello", "world"], ["jello", "word", "help"], n=2), [
+                         ["jello", "help"], ["word", "jello"]])
+        with self.assertRaises(ValueError):
+            n_closest_many(["hello"], ["jello", "word"], n=0)
+        with self.assertRaises(ValueError):
+            n_closest_many(["hello"], ["jello", "word"], n=3)
+        with self.assertRaises(ValueError):
+            n_closest_many(["hello"], ["jello", "word"], n=1, algorithm="unknown")
+
+    def test_cdist(self):
+        self.assertEqual([list(row) for row in cdist(["hello", "world"], ["jello", "word", "help"])], [
+                         [1, 5, 2], [4, 1, 4]])
+        self.assertEqual([list(row) for row in cdist(["travel"], ["gravel", "travel"], algorithm="hamming")], [
+                         [1, 0]])
+        self.assertEqual([[round(score, 2) for score in row] for row in cdist(
+            ["a"], ["s", "z"], algorithm="typo", layout="azerty")], [[0.8, 0.5]])
+        self.assertEqual(len(cdist([], ["hello"])), 0)
+        with self.assertRaises(ValueError):
+            cdist(["travel"], ["gravel", "gated"], algorithm="hamming")
+        with self.assertRaises(ValueError):
+            cdist(["travel"], ["gravel"], algorithm="unknown")
+
+    def test_threads(self):
+        options = ["word%d" % i for i in range(2000)]
+        expected = [closest("word%d" % i, options) for i in range(8)]
+        results = [None] * 8
+
+        def run(i):
+            results[i] = closest("word%d" % i, options)
+
+        threads = [threading.Thread(target=run, args=(i,)) for i in range(8)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        self.assertEqual(results, expected)
+
+    def test_gil_released(self):
+        queries = ["word%d" % i for i in range(50)]
+        choices = ["word%d" % i for i in range(5000)]
+        calls = [
+            lambda: cdist(queries, choices),
+            lambda: dedupe(choices[:1000]),
+            lambda: fuzzy_join(queries, choices),
+        ]
+        for call in calls:
+            ticks = []
+            done = threading.Event()
+
+            def tick():
+                while not done.is_set():
+                    ticks.append(time.monotonic())
+                    time.sleep(0.001)
+
+            thread = threading.Thread(target=tick)
+            thread.start()
+            try:
+                start = time.monotonic()
+                call()
+                end = time.monotonic()
+            finally:
+                done.set()
+                thread.join()
+            # The ticking thread needs the GIL to record a tick, so it only keeps going if the call releases it.
+            self.assertGreaterEqual(len([t for t in ticks if start < t < end]), 3)
+
+    def test_thread_settings(self):
+        options = ["word%d" % i for i in range(3000)]
+
+        def scores(matches):
+            return [score for _, score in matches]
+
+        expected = scores(n_closest_with_score("word42", options, n=5))
+        expected_groups = dedupe(options[:200], cutoff=1)
+        expected_join = fuzzy_join(["word42", "word7"], options)
+        expected_labels = cluster(options[:200], cutoff=1), cluster(options[:200], cutoff=1, method="leader")
+        trie, qgrams = Trie(options), QGramIndex(options)
+        expected_searches = trie.search("word42", 1), qgrams.search("word42", 1)
+        self.assertEqual(get_parallel_threshold(), 1000)
+        try:
+            set_num_threads(1)
+            self.assertEqual(get_num_threads(), 1)
+            self.assertEqual(scores(n_closest_with_score("word42", options, n=5)), expected)
+            self.assertEqual(dedupe(options[:200], cutoff=1), expected_groups)
+            self.assertEqual(fuzzy_join(["word42", "word7"], options), expected_join)
+            self.assertEqual((trie.search("word42", 1), qgrams.search("word42", 1)), expected_searches)
+            set_num_threads(3)
+            self.assertEqual(get_num_threads(), 3)
+            for threshold in [0, 10 ** 9]:
+                set_parallel_threshold(threshold)
+                self.assertEqual(get_parallel_threshold(), threshold)
+                self.assertEqual(scores(n_closest_with_score("word42", options, n=5)), expected)
+                self.assertEqual(dedupe(options[:200], cutoff=1), expected_groups)
+                self.assertEqual((cluster(options[:200], cutoff=1), cluster(options[:200], cutoff=1, method="leader")),
+                                 expected_labels)
+                self.assertEqual(fuzzy_join(["word42", "word7"], options), expected_join)
+            self.assertEqual(closest_many(["word1", "word2"], options), ["word1", "word2"])
+        finally:
+            set_num_threads(0)
+            set_parallel_threshold(1000)
+        self.assertGreaterEqual(get_num_threads(), 1)
+
 
 if __name__ == '__main__':
     unittest.main()
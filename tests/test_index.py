@@ -0,0 +1,366 @@
+import os
+import random
+import struct
+import tempfile
+import threading
+import time
+import unittest
+import zlib
+
+from ffzf import Index, BKTree, SymSpell, Trie, QGramIndex, closest, n_closest, n_closest_with_score
+
+
+class TestIndex(unittest.TestCase):
+
+    def test_index(self):
+        index = Index(["hello", "world", "Jello", "yellow"])
+        self.assertEqual(len(index), 4)
+        self.assertEqual(index.candidates, ["hello", "world", "Jello", "yellow"])
+        self.assertFalse(index.case_sensitive)
+        self.assertFalse(index.remove_whitespace)
+        self.assertEqual(len(Index([])), 0)
+
+    def test_index_closest(self):
+        index = Index(["gravel", "gambit", "gated"])
+        self.assertEqual(index.closest("travel"), "gravel")
+        self.assertEqual(index.closest("travel", algorithm="jaro"), "gravel")
+        self.assertEqual(index.closest("travel", algorithm="jarowinkler"),
+                         closest("travel", ["gravel", "gambit", "gated"], algorithm="jarowinkler"))
+        self.assertEqual(index.closest_with_score("travel"), ("gravel", 1))
+        self.assertEqual(Index(["HELLO", "help"]).closest("hello"), "HELLO")
+        self.assertEqual(Index(["HELLO", "help"], case_sensitive=True).closest("hello"), "help")
+        self.assertEqual(Index(["he llo", "help"], remove_whitespace=True).closest("hello"), "he llo")
+        ties = Index(["bbbx", "bxb", "bb"])
+        self.assertEqual(ties.closest("bbb"), "bbbx")
+        self.assertEqual(ties.closest("bbb", tie_break="shortest"), "bb")
+        self.assertEqual(ties.n_closest("bbb", 3, tie_break="lexicographic"), ["bb", "bbbx", "bxb"])
+        self.assertEqual(ties.n_closest("bbb", 3, tie_break="shortest"),
+                         n_closest("bbb", ["bbbx", "bxb", "bb"], 3, tie_break="shortest"))
+        self.assertEqual(Index(["s", "z"]).closest("a", algorithm="typo"), "s")
+        self.assertEqual(Index(["s", "z"]).closest("a", algorithm="typo", layout="azerty"), "z")
+        self.assertEqual(Index(["s", "z"]).closest_with_score("a", algorithm="typo", layout=["zsa"]), ("s", 0.5))
+        with self.assertRaises(ValueError):
+            index.closest("travel", algorithm="unknown")
+        with self.assertRaises(ValueError):
+            index.closest("travel", algorithm="hamming")
+        with self.assertRaises(ValueError):
+            Index([]).closest("travel")
+        with self.assertRaises(ValueError):
+            index.closest("travel", algorithm="jarowinkler", threshold=1.5)
+        with self.assertRaises(ValueError):
+            index.closest("travel", tie_break="longest")
+
+    def test_index_n_closest(self):
+        candidates = ["yello", "jello", "harps", "languid"]
+        index = Index(candidates)
+        self.assertEqual(index.n_closest("hello", 3), ["yello", "jello", "harps"])
+        self.assertEqual(Index(["excitement", "elation", "joyful"]).n_closest_with_score("euphoria", 2),
+                         n_closest_with_score("euphoria", ["excitement", "elation", "joyful"], 2))
+        with self.assertRaises(ValueError):
+            index.n_closest("hello", 0)
+        with self.assertRaises(ValueError):
+            index.n_closest("hello", 5)
+
+    def test_bktree_search(self):
+        words = ["hello", "help", "hell", "shell", "yellow", "hello", "world"]
+        tree = BKTree(words)
+        self.assertEqual(len(tree), 7)
+        self.assertEqual(tree.algorithm, "LEVENSHTEIN")
+        self.assertEqual(tree.search("hello", 0), [("hello", 0), ("hello", 0)])
+        self.assertEqual(tree.search("hello", 1), [
+                         ("hello", 0), ("hello", 0), ("hell", 1)])
+        self.assertEqual(tree.search("HELO", 1), [("hello", 1), ("help", 1), ("hell", 1), ("hello", 1)])
+        self.assertEqual(BKTree(words, algorithm="damerau").search("ehllo", 1), [("hello", 1), ("hello", 1)])
+        self.assertEqual(BKTree(words, algorithm="hamming").search("jello", 1), [("hello", 1), ("hello", 1)])
+        self.assertEqual(BKTree(words, algorithm="hamming").search("hi", 1), [])
+        self.assertEqual(BKTree([]).search("hello", 3), [])
+        with self.assertRaises(ValueError):
+            BKTree(words, algorithm="jaro")
+
+    def test_bktree_nearest(self):
+        tree = BKTree(["hello", "help", "hell", "shell", "yellow", "world"])
+        self.assertEqual(tree.nearest("hello", 1), [("hello", 0)])
+        self.assertEqual(tree.nearest("hello", 3), [("hello", 0), ("hell", 1), ("help", 2)])
+        self.assertEqual(len(tree.nearest("hello", 10)), 6)
+        self.assertEqual(len(tree.nearest("hello", 2 ** 62)), 6)
+        with self.assertRaises(ValueError):
+            tree.nearest("hello", 0)
+
+    def test_symspell_lookup(self):
+        index = SymSpell(["hello", "help", "hell", "Hello", "world"], frequencies=[5, 10, 1, 2, 3])
+        self.assertEqual(len(index), 4)
+        self.assertEqual(index.max_distance, 2)
+        self.assertEqual(index.lookup("helo"), [("help", 1), ("hello", 1), ("hell", 1)])
+        self.assertEqual(index.lookup("hel", 1), [("help", 1), ("hell", 1)])
+        self.assertEqual(index.lookup("hello", 0), [("hello", 0)])
+        self.assertEqual(SymSpell(["hello", "help"]).lookup("helo"), [("hello", 1), ("help", 1)])
+        self.assertEqual(SymSpell(["abcdef"], max_distance=1).lookup("abcxyz", 1), [])
+        self.assertEqual(SymSpell(["abc"], max_distance=2 ** 40).lookup("xyz"), [("abc", 3)])
+        self.assertEqual(SymSpell(["abc", "ABC"], frequencies=[2 ** 64 - 1, 1]).lookup("abc"), [("abc", 0)])
+        with self.assertRaises(ValueError):
+            index.lookup("helo", 3)
+        with self.assertRaises(ValueError):
+            SymSpell(["hello", "help"], frequencies=[1])
+
+    def test_symspell_correct(self):
+        index = SymSpell(["hello", "help", "hell", "world"], frequencies=[5, 10, 1, 3])
+        self.assertEqual(index.correct("helo"), "help")
+        self.assertEqual(index.correct("hello"), "hello")
+        self.assertEqual(index.correct("wrold"), "world")
+        self.assertIsNone(index.correct("xyzzy"))
+
+    def test_trie_search(self):
+        words = ["hello", "help", "hell", "shell", "yellow", "hello", "world", ""]
+        trie = Trie(words)
+        self.assertEqual(len(trie), 8)
+        self.assertEqual(trie.search("hello", 0), [("hello", 0), ("hello", 0)])
+        self.assertEqual(trie.search("hello", 1), [("hello", 0), ("hello", 0), ("hell", 1)])
+        self.assertEqual(trie.search("HELO", 1), [("hello", 1), ("help", 1), ("hell", 1), ("hello", 1)])
+        self.assertEqual(trie.search("hi", 2), [("", 2)])
+        self.assertEqual(Trie(["Hello"], case_sensitive=True).search("hello", 0), [])
+        self.assertEqual(Trie([]).search("hello", 3), [])
+        long_word = "a" * 200000
+        self.assertEqual(Trie([long_word]).search("a", len(long_word)), [(long_word, len(long_word) - 1)])
+
+    def test_trie_closest(self):
+        words = ["hello", "help", "hell", "shell", "yellow", "world"]
+        trie = Trie(words)
+        self.assertEqual(trie.closest("helo"), "hello")
+        self.assertEqual(trie.closest_with_score("wrld"), ("world", 1))
+        self.assertEqual(trie.n_closest("hello", 3), ["hello", "hell", "help"])
+        self.assertEqual(trie.n_closest_with_score("xyz", 6), n_closest_with_score("xyz", words, 6))
+        with self.assertRaises(ValueError):
+            trie.n_closest("hello", 0)
+        with self.assertRaises(ValueError):
+            trie.n_closest("hello", 7)
+        with self.assertRaises(ValueError):
+            Trie([]).closest("hello")
+
+    def test_qgram_search(self):
+        words = ["12 Main Street", "12 Maine Street", "400 Elm Avenue", "12 main street", "ab", ""]
+        index = QGramIndex(words)
+        self.assertEqual(len(index), 6)
+        self.assertEqual(index.q, 3)
+        self.assertEqual(index.search("12 Main Street", 0), [("12 Main Street", 0), ("12 main street", 0)])
+        self.assertEqual(index.search("12 MAIN St.", 4), [("12 Main Street", 4), ("12 main street", 4)])
+        self.assertEqual(index.search("12 Main Street", 1), [
+                         ("12 Main Street", 0), ("12 main street", 0), ("12 Maine Street", 1)])
+        self.assertEqual(index.search("xy", 2), [("ab", 2), ("", 2)])
+        self.assertEqual(QGramIndex(words, q=1).search("a", 1), [("ab", 1), ("", 1)])
+        self.assertEqual(QGramIndex([]).search("hello", 3), [])
+        self.assertEqual(QGramIndex(["abc", "abcdef", "xyz"]).search("abc", 2 ** 64 - 1), [
+                         ("abc", 0), ("abcdef", 3), ("xyz", 3)])
+        with self.assertRaises(ValueError):
+            QGramIndex(words, q=0)
+
+    def test_qgram_n_closest(self):
+        words = ["12 Main Street", "400 Elm Avenue", "12 Maine Street", "zzz"]
+        index = QGramIndex(words)
+        self.assertEqual(index.n_closest("12 main st", 2), ["12 Main Street", "12 Maine Street"])
+        self.assertEqual(index.n_closest_with_score("400 elm ave", 1), [("400 Elm Avenue", 3)])
+        self.assertEqual(index.n_closest("12 Main Street", 4, min_shared=5), ["12 Main Street", "12 Maine Street"])
+        self.assertEqual(index.n_closest("12 Maine Street", 1, algorithm="jarowinkler"), ["12 Maine Street"])
+        self.assertEqual(index.n_closest("qqq", 1), [])
+        self.assertEqual(QGramIndex(["sb", "zb"], q=1).n_closest("ab", 1, algorithm="typo"), ["sb"])
+        self.assertEqual(QGramIndex(["sb", "zb"], q=1).n_closest("ab", 1, algorithm="typo", layout="azerty"), ["zb"])
+        with self.assertRaises(ValueError):
+            index.n_closest("12 Main Street", 0)
+        with self.assertRaises(ValueError):
+            index.n_closest("12 Main Street", 5)
+        with self.assertRaises(ValueError):
+            index.n_closest("12 Main Street", 1, algorithm="foo")
+
+    def test_save_load(self):
+        words = ["hello", "help", "hell", "shell", "yellow", "Hello", "world"]
+        indexes = [Index(words, case_sensitive=True), BKTree(words, algorithm="damerau"),
+                   SymSpell(words, frequencies=[1, 2, 3, 4, 5, 6, 7]), Trie(words), QGramIndex(words, q=2)]
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "index.ffzf")
+            for index in indexes:
+                index.save(path)
+                loaded = type(index).load(path)
+                self.assertEqual(len(loaded), len(index))
+                if isinstance(index, SymSpell):
+                    self.assertEqual(loaded.lookup("helo"), index.lookup("helo"))
+                elif isinstance(index, Index):
+                    self.assertEqual(loaded.candidates, words)
+                    self.assertEqual(loaded.n_closest_with_score("Helo", 3), index.n_closest_with_score("Helo", 3))
+                else:
+                    self.assertEqual(loaded.search("helo", 2), index.search("helo", 2))
+
+    def test_load_errors(self):
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "index.ffzf")
+            Trie(["hello", "world"]).save(path)
+            with self.assertRaisesRegex(ValueError, "does not hold a BKTree"):
+                BKTree.load(path)
+            with open(path, "rb") as file:
+                contents = bytearray(file.read())
+            with open(path, "wb") as file:
+                file.write(contents[:4] + (99).to_bytes(4, "little") + contents[8:])
+            with self.assertRaisesRegex(ValueError, "version: 99"):
+                Trie.load(path)
+            # Version 1 files predate removable candidates and are laid out differently.
+            with open(path, "wb") as file:
+                file.write(contents[:4] + (1).to_bytes(4, "little") + contents[8:])
+            with self.assertRaisesRegex(ValueError, "version: 1\\. This version of ffzf reads version 2 files"):
+                Trie.load(path)
+            contents[-1] ^= 1
+            with open(path, "wb") as file:
+                file.write(contents)
+            with self.assertRaisesRegex(ValueError, "checksum"):
+                Trie.load(path)
+            with open(path, "wb") as file:
+                file.write(b"hello")
+            with self.assertRaisesRegex(ValueError, "Not an ffzf index file"):
+                Trie.load(path)
+            with self.assertRaises(OSError):
+                Trie.load(os.path.join(directory, "missing.ffzf"))
+
+    def test_load_corrupt(self):
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "index.ffzf")
+            Trie(["a"]).save(path)
+            with open(path, "rb") as file:
+                contents = file.read()
+            # The root's only child, stored as a (character, node) pair after the header.
+            child = struct.pack("<IQ", ord("a"), 1)
+            for node, message in [(5, "node 5 is out of range"), (0, "node 0 is a child of node 0")]:
+                payload = contents[24:].replace(child, struct.pack("<IQ", ord("a"), node))
+                with open(path, "wb") as file:
+                    file.write(contents[:20] + struct.pack("<I", zlib.crc32(payload)) + payload)
+                with self.assertRaisesRegex(ValueError, "Corrupt index file: " + message):
+                    Trie.load(path)
+
+    def test_add_remove_update(self):
+        words = ["hello", "help", "hell", "world", "hello"]
+        for index in [Index(words), BKTree(words), Trie(words), QGramIndex(words)]:
+            index.add("yellow")
+            self.assertEqual(len(index), 6)
+            index.remove("hello")
+            self.assertEqual(len(index), 4)
+            index.update("hell", "shell")
+            self.assertEqual(len(index), 4)
+            with self.assertRaises(ValueError):
+                index.remove("hello")
+            with self.assertRaises(ValueError):
+                index.update("hell", "jello")
+            if isinstance(index, Index):
+                self.assertEqual(index.candidates, ["help", "shell", "world", "yellow"])
+            else:
+                self.assertEqual(index.search("hello", 2), [("help", 2), ("shell", 2), ("yellow", 2)])
+
+    def test_symspell_add_remove_update(self):
+        speller = SymSpell(["hello", "help", "world"], frequencies=[1, 10, 3])
+        speller.add("helo")
+        self.assertEqual(len(speller), 4)
+        self.assertEqual(speller.lookup("helo", 0), [("helo", 0)])
+        speller.add("HELLO", frequency=20)
+        self.assertEqual(len(speller), 4)
+        self.assertEqual(speller.lookup("hellp", 1), [("hello", 1), ("help", 1)])
+        speller.remove("help")
+        self.assertEqual(len(speller), 3)
+        self.assertEqual(speller.lookup("hellp", 1), [("hello", 1)])
+        speller.update("world", "word")
+        self.assertEqual(speller.correct("world"), "word")
+        speller.update("helo", "hello")
+        self.assertEqual(len(speller), 2)
+        self.assertEqual(speller.lookup("helo"), [("hello", 1)])
+        with self.assertRaises(ValueError):
+            speller.remove("help")
+        with self.assertRaises(ValueError):
+            speller.update("help", "yelp")
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "index.ffzf")
+            speller.save(path)
+            loaded = SymSpell.load(path)
+            self.assertEqual(len(loaded), 2)
+            self.assertEqual(loaded.lookup("helo"), speller.lookup("helo"))
+
+    def test_updates_match_rebuild(self):
+        rng = random.Random(7)
+        words = ["".join(rng.choice("abc") for _ in range(rng.randint(0, 6))) for _ in range(60)]
+        current = list(words)
+        indexes = [BKTree(words, algorithm="damerau"), Trie(words), QGramIndex(words, q=2), SymSpell(words)]
+        for step in range(120):
+            word = "".join(rng.choice("abc") for _ in range(rng.randint(0, 6)))
+            if step % 3 == 0 or word not in current:
+                current.append(word)
+                for index in indexes:
+                    index.add(word)
+            elif step % 3 == 1:
+                current = [candidate for candidate in current if candidate != word]
+                for index in indexes:
+                    index.remove(word)
+            else:
+                new = word + rng.choice("abc")
+                current = [new if candidate == word else candidate for candidate in current]
+                for index in indexes:
+                    index.update(word, new)
+        rebuilt = [BKTree(current, algorithm="damerau"), Trie(current), QGramIndex(current, q=2)]
+        for index, fresh in zip(indexes, rebuilt):
+            self.assertEqual(len(index), len(current))
+            for query in ["", "a", "abc", "cab", "bbbb"]:
+                self.assertEqual(sorted(index.search(query, 2)), sorted(fresh.search(query, 2)))
+        # SymSpell merges copies of a word, adding up their frequencies, so it holds each distinct word once.
+        speller, fresh = indexes[-1], SymSpell(current)
+        self.assertEqual(len(speller), len(set(current)))
+        for query in ["", "a", "abc", "cab", "bbbb"]:
+            self.assertEqual(sorted(speller.lookup(query)), sorted(fresh.lookup(query)))
+
+    def test_concurrent_updates(self):
+        index = Index(["hello", "help"])
+        errors = []
+
+        def query():
+            try:
+                for _ in range(200):
+                    self.assertIn(index.closest("hello"), ["hello", "help"])
+            except Exception as error:
+                errors.append(error)
+
+        threads = [threading.Thread(target=query) for _ in range(4)]
+        for thread in threads:
+            thread.start()
+        for i in range(200):
+            index.add("word{}".format(i))
+            index.update("word{}".format(i), "other{}".format(i))
+        for thread in threads:
+            thread.join()
+        self.assertEqual(errors, [])
+        self.assertEqual(len(index), 202)
+
+    def test_gil_released(self):
+        rng = random.Random(1)
+        words = ["".join(rng.choice("abcdefgh") for _ in range(rng.randint(5, 12))) for _ in range(50000)]
+        index, tree, trie, qgrams = Index(words), BKTree(words), Trie(words), QGramIndex(words)
+        searches = [
+            lambda: index.n_closest("abcdefgh", 5, algorithm="jarowinkler"),
+            lambda: tree.search("abcdefgh", 4),
+            lambda: trie.search("abcdefgh", 4),
+            lambda: qgrams.search("abcdefgh", 4),
+        ]
+        for search in searches:
+            ticks = []
+            done = threading.Event()
+
+            def tick():
+                while not done.is_set():
+                    ticks.append(time.monotonic())
+                    time.sleep(0.001)
+
+            thread = threading.Thread(target=tick)
+            thread.start()
+            try:
+                start = time.monotonic()
+                search()
+                end = time.monotonic()
+            finally:
+                done.set()
+                thread.join()
+            # The ticking thread needs the GIL to record a tick, so it only keeps going if the search releases it.
+            self.assertGreaterEqual(len([t for t in ticks if start < t < end]), 3)
+
+
+if __name__ == '__main__':
+    unittest.main()
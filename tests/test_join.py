@@ -0,0 +1,51 @@
+import unittest
+
+from ffzf import fuzzy_join, assignment
+
+
+class TestJoiningFunctions(unittest.TestCase):
+
+    def test_fuzzy_join(self):
+        left = ["Acme Inc", "Globex", "Initech", "ACME"]
+        right = ["Globex Corp", "acme inc.", "Umbrella"]
+        self.assertEqual(fuzzy_join(left, right), [
+                         (0, 1, 1), (1, 0, 5), (2, 2, 7), (3, 1, 5)])
+        self.assertEqual(fuzzy_join(left, right, cutoff=5), [
+                         (0, 1, 1), (1, 0, 5), (3, 1, 5)])
+        self.assertEqual(fuzzy_join(left, right, cutoff=5, how="left"), [
+                         (0, 1, 1), (1, 0, 5), (2, None, None), (3, 1, 5)])
+        self.assertEqual(fuzzy_join(left, right, how="left", one_to_one=True), [
+                         (0, 1, 1), (1, 0, 5), (2, 2, 7), (3, None, None)])
+        self.assertEqual([row[:2] for row in fuzzy_join(left, right, algorithm="jarowinkler", cutoff=0.8)], [
+                         (0, 1), (1, 0), (3, 1)])
+        self.assertEqual(fuzzy_join(["abc"], ["ab", "abd"], algorithm="hamming"), [(0, 1, 1)])
+        self.assertEqual(fuzzy_join(left, []), [])
+        self.assertEqual(fuzzy_join(["a"], ["s", "z"], algorithm="typo"), [(0, 0, 0.5)])
+        self.assertEqual(fuzzy_join(["a"], ["s", "z"], algorithm="typo", cutoff=0.5, layout="azerty"), [(0, 1, 0.5)])
+        self.assertEqual(fuzzy_join([], right), [])
+        with self.assertRaises(ValueError):
+            fuzzy_join(left, right, algorithm="unknown")
+        with self.assertRaises(ValueError):
+            fuzzy_join(left, right, how="outer")
+
+    def test_assignment(self):
+        left = ["Acme Inc", "ACME", "Globex"]
+        right = ["acme", "Acme Incorporated", "Globex Corp"]
+        self.assertEqual(assignment(left, right), [(0, 1, 9), (1, 0, 0), (2, 2, 5)])
+        self.assertEqual(assignment(["dc", "db"], ["cd", "ccd"]), [(0, 1, 2), (1, 0, 2)])
+        self.assertEqual(fuzzy_join(["dc", "db"], ["cd", "ccd"], one_to_one=True), [(0, 0, 2), (1, 1, 3)])
+        self.assertEqual(assignment(left, right[:2]), [(0, 1, 9), (1, 0, 0)])
+        self.assertEqual(assignment(left[:1], right), [(0, 0, 4)])
+        self.assertEqual([row[:2] for row in assignment(left, right, algorithm="jarowinkler")], [
+                         (0, 1), (1, 0), (2, 2)])
+        self.assertEqual(assignment([], right), [])
+        self.assertEqual(assignment(["a"], ["s", "z"], algorithm="typo"), [(0, 0, 0.5)])
+        self.assertEqual(assignment(["a"], ["s", "z"], algorithm="typo", layout="azerty"), [(0, 1, 0.5)])
+        with self.assertRaises(ValueError):
+            assignment(left, right, algorithm="unknown")
+        with self.assertRaises(ValueError):
+            assignment(left, right, algorithm="hamming")
+
+
+if __name__ == '__main__':
+    unittest.main()
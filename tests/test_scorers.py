@@ -2,9 +2,11 @@ import unittest
 
 from ffzf import (
     levenshtein_distance,
+    damerau_levenshtein_distance,
     jaro_similarity,
     jaro_winkler_similarity,
-    hamming_distance
+    hamming_distance,
+    typo_distance
 )
 
 
@@ -27,6 +29,15 @@ class TestScoringFunctions(unittest.TestCase):
             24.0
         )
 
+    def test_damerau_levenshtein_distance(self):
+        self.assertEqual(damerau_levenshtein_distance("", ""), 0)
+        self.assertEqual(damerau_levenshtein_distance("a", ""), 1)
+        self.assertEqual(damerau_levenshtein_distance("hello", "ehllo"), 1)
+        self.assertEqual(damerau_levenshtein_distance("ca", "abc"), 2)
+        self.assertEqual(damerau_levenshtein_distance("kitten", "sitting"), 3)
+        self.assertEqual(damerau_levenshtein_distance("Hello", "eHllo"), 1)
+        self.assertEqual(damerau_levenshtein_distance("Hello", "ehllo", case_sensitive=True), 2)
+
     def test_jaro_similarity(self):
         self.assertAlmostEqual(jaro_similarity(
             "subprime", "primers"), 0.779762, places=2)
@@ -65,6 +76,19 @@ class TestScoringFunctions(unittest.TestCase):
             hamming_distance("a short string",
                              "a a string longer than a short string")
 
+    def test_typo_distance(self):
+        self.assertEqual(typo_distance("", ""), 0.0)
+        self.assertEqual(typo_distance("hello", "hello"), 0.0)
+        self.assertEqual(typo_distance("hello", "hell"), 1.0)
+        self.assertEqual(typo_distance("hello", "jello"), 0.5)
+        self.assertEqual(typo_distance("hello", "hpllo"), 1.0)
+        self.assertAlmostEqual(typo_distance("azerty", "qzerty"), 0.515388, places=4)
+        self.assertEqual(typo_distance("hello", "jello", layout="dvorak"), 1.0)
+        self.assertEqual(typo_distance("ab", "ba", layout=["ab"]), 1.0)
+        with self.assertRaises(ValueError):
+            typo_distance("hello", "jello", layout="colemak")
+        with self.assertRaises(ValueError):
+            typo_distance("hello", "jello", layout=[])
 
     def test_scorer_case_sensitive(self):
         self.assertEqual(levenshtein_distance("a", "A", case_sensitive=True), 1.0)